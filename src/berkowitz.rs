@@ -1,62 +1,272 @@
-use crate::gf2int::GF2Int;
-use ndarray::{arr2, s, Array2};
+use crate::{
+    berlekamp_massey::{berlekamp_massey, output_sequence},
+    gf2int::GF2Int,
+    rng::RNG,
+};
+use ndarray::{s, Array2, ArrayView1, ArrayView2};
+use num_traits::{One, Zero};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::ops::{Mul, Sub};
+
+// commutative ring operations berkowitz needs, it never divides so integers
+// mod m and big rationals work as well as GF(2)
+pub trait Ring: Clone + Zero + One + Sub<Output = Self> + Mul<Output = Self> {}
+
+impl<T: Clone + Zero + One + Sub<Output = T> + Mul<Output = T>> Ring for T {}
 
 pub trait CharPoly {
     type NumberType;
     fn compute_charpoly_coeffs(&mut self) -> Vec<Self::NumberType>;
 }
 
-impl CharPoly for Array2<GF2Int> {
-    type NumberType = GF2Int;
+impl<T: Ring> CharPoly for Array2<T> {
+    type NumberType = T;
 
     fn compute_charpoly_coeffs(&mut self) -> Vec<Self::NumberType> {
-        let berk_mat = berkowitz_vector(self);
+        berkowitz_vector(self)
+    }
+}
 
-        let mut coeffs = vec![];
-        for i in 0..berk_mat.shape()[0] {
-            coeffs.push(berk_mat[[i, 0]]);
+fn dot<'a, 'b, T: Ring + 'a + 'b>(
+    lhs: impl IntoIterator<Item = &'a T>,
+    rhs: impl IntoIterator<Item = &'b T>,
+) -> T {
+    lhs.into_iter()
+        .zip(rhs)
+        .fold(T::zero(), |acc, (a, b)| acc + a.clone() * b.clone())
+}
+
+fn mat_vec_into<T: Ring>(matrix: &ArrayView2<T>, vector: &[T], result: &mut Vec<T>) {
+    result.clear();
+    result.extend(matrix.rows().into_iter().map(|row| dot(row, vector)));
+}
+
+// diagonals of the toeplitz matrix of ``matrix[k.., k..]`` into ``diags``,
+// the products ``sub_a ** i * c`` alternating between ``krylov`` and
+// ``scratch`` so that each step reuses the last product and no buffer is
+// reallocated across the submatrices
+// matrix = [
+//     [a, r],
+//     [c, sub_a]
+// ]
+fn berkowitz_toeplitz_diags<T: Ring>(
+    matrix: &Array2<T>,
+    k: usize,
+    diags: &mut Vec<T>,
+    krylov: &mut Vec<T>,
+    scratch: &mut Vec<T>,
+) {
+    let a = matrix[[k, k]].clone();
+    let r: ArrayView1<T> = matrix.slice(s![k, k + 1..]);
+    let sub_a = matrix.slice(s![k + 1.., k + 1..]);
+    krylov.clear();
+    krylov.extend(matrix.slice(s![k + 1.., k]).iter().cloned());
+    diags.clear();
+    diags.extend([T::one(), T::zero() - a]);
+    for i in 0..sub_a.shape()[0] {
+        if i != 0 {
+            mat_vec_into(&sub_a, krylov, scratch);
+            std::mem::swap(krylov, scratch);
         }
+        diags.push(T::zero() - dot(r, krylov.iter()));
+    }
+}
 
-        coeffs
+fn berkowitz_vector<T: Ring>(matrix: &Array2<T>) -> Vec<T> {
+    let size = matrix.shape()[0];
+    let mut vector = Vec::with_capacity(size + 1);
+    vector.push(T::one());
+    let mut diags = Vec::with_capacity(size + 1);
+    let mut krylov = Vec::with_capacity(size);
+    let mut scratch = Vec::with_capacity(size);
+    // multiply the toeplitz matrices from the bottom right submatrix up
+    // without materializing them, in place from the highest coefficient down
+    // as each only reads the lower ones
+    for k in (0..size).rev() {
+        berkowitz_toeplitz_diags(matrix, k, &mut diags, &mut krylov, &mut scratch);
+        vector.push(T::zero());
+        for i in (0..vector.len()).rev() {
+            vector[i] = (0..=i).fold(T::zero(), |acc, j| {
+                acc + diags[i - j].clone() * vector[j].clone()
+            });
+        }
     }
+    vector
+}
+
+pub trait MinPoly {
+    fn compute_minpoly_coeffs(&self) -> Vec<GF2Int>;
 }
 
-fn berkowitz_toeplitz_matrix(matrix: &mut Array2<GF2Int>) -> (Array2<GF2Int>, Array2<GF2Int>) {
-    // matrix = [
-    //     [a, r],
-    //     [c, sub_a]
-    // ]
-    let (a, r) = (
-        matrix[[0, 0]],
-        matrix.slice_mut(s![0usize, 1usize..]).to_owned(),
-    );
-    let (c, sub_a) = (
-        matrix.slice_mut(s![1usize.., 0usize]).to_owned(),
-        matrix.slice_mut(s![1usize.., 1usize..]).to_owned(),
-    );
-    let mut diag_vecs = vec![c];
-    for i in 0..matrix.shape()[0] - 2 {
-        diag_vecs.push(sub_a.dot(&diag_vecs[i]));
+impl MinPoly for Array2<GF2Int> {
+    // projects the orbit of a random state onto a random bit mask and runs
+    // berlekamp-massey on the resulting sequence, this yields the minimal
+    // polynomial unless the projection is unlucky (probability ~2^-n for
+    // primitive matrices)
+    fn compute_minpoly_coeffs(&self) -> Vec<GF2Int> {
+        let size = self.shape()[0];
+        let limbs = size.div_ceil(64);
+        let columns = pack_columns(self);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut state: Vec<u64> = (0..limbs).map(|_| rng.gen()).collect();
+        let projection: Vec<u64> = (0..limbs).map(|_| rng.gen()).collect();
+        if !size.is_multiple_of(64) {
+            state[limbs - 1] &= (1 << (size % 64)) - 1;
+        }
+
+        let mut sequence = Vec::with_capacity(2 * size);
+        for _ in 0..2 * size {
+            sequence.push(GF2Int::new(packed_parity(&state, &projection)));
+            let mut next_state = vec![0u64; limbs];
+            for (i, column) in columns.iter().enumerate() {
+                next_state[i / 64] |= (packed_parity(&state, column) as u64) << (i % 64);
+            }
+            state = next_state;
+        }
+        berlekamp_massey(&sequence)
     }
-    let mut diags = vec![GF2Int::new(1), a];
-    for d in diag_vecs {
-        diags.push(r.dot(&d));
+}
+
+// minimal polynomial coefficients of a generator from ``2 * size`` output
+// bits of its orbit, never building the size x size transition matrix that
+// 19937-bit generators make impractical; it is the minimal polynomial of the
+// generator whenever the output bit does not hide part of the orbit
+pub fn generator_minpoly_coeffs<R: RNG>(
+    rng: &mut R,
+    size: usize,
+    output_bit: impl Fn(&mut R) -> GF2Int,
+) -> Vec<GF2Int> {
+    berlekamp_massey(&output_sequence(rng, 2 * size, output_bit))
+}
+
+fn pack_columns(matrix: &Array2<GF2Int>) -> Vec<Vec<u64>> {
+    let (height, width) = matrix.dim();
+    (0..width)
+        .map(|j| {
+            let mut column = vec![0u64; height.div_ceil(64)];
+            for i in 0..height {
+                column[i / 64] |= (matrix[[i, j]].val as u64) << (i % 64);
+            }
+            column
+        })
+        .collect()
+}
+
+fn packed_parity(lhs: &[u64], rhs: &[u64]) -> u8 {
+    let ones: u32 = lhs.iter().zip(rhs).map(|(a, b)| (a & b).count_ones()).sum();
+    (ones & 1) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{poly_analysis::charpoly, xoroshiro::Xoroshiro64Star};
+    use ndarray::arr2;
+    use num_rational::BigRational;
+    use std::ops::Add;
+
+    // integers mod 12, zero divisors and all
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    struct Mod12(i64);
+
+    impl Add for Mod12 {
+        type Output = Mod12;
+
+        fn add(self, rhs: Mod12) -> Mod12 {
+            Mod12((self.0 + rhs.0).rem_euclid(12))
+        }
     }
-    let mut toeplitz = Array2::<GF2Int>::zeros((matrix.shape()[1] + 1, matrix.shape()[0]));
-    for i in 0..toeplitz.shape()[0] {
-        for j in 0..toeplitz.shape()[1] {
-            toeplitz[[i, j]] = if j > i { GF2Int::new(0) } else { diags[i - j] }
+
+    impl Sub for Mod12 {
+        type Output = Mod12;
+
+        fn sub(self, rhs: Mod12) -> Mod12 {
+            Mod12((self.0 - rhs.0).rem_euclid(12))
         }
     }
-    (sub_a, toeplitz)
-}
 
-fn berkowitz_vector(matrix: &mut Array2<GF2Int>) -> Array2<GF2Int> {
-    if matrix.shape() == &[0, 0] {
-        return arr2(&[[GF2Int::new(1)]]);
-    } else if matrix.shape() == &[1, 1] {
-        return arr2(&[[GF2Int::new(1)], [matrix[[0, 0]]]]);
+    impl Mul for Mod12 {
+        type Output = Mod12;
+
+        fn mul(self, rhs: Mod12) -> Mod12 {
+            Mod12((self.0 * rhs.0).rem_euclid(12))
+        }
+    }
+
+    impl Zero for Mod12 {
+        fn zero() -> Mod12 {
+            Mod12(0)
+        }
+
+        fn is_zero(&self) -> bool {
+            self.0 == 0
+        }
+    }
+
+    impl One for Mod12 {
+        fn one() -> Mod12 {
+            Mod12(1)
+        }
+    }
+
+    #[test]
+    fn charpoly_over_integers_and_rationals() {
+        let mut integers = arr2(&[[1i64, 2], [3, 4]]);
+        assert_eq!(integers.compute_charpoly_coeffs(), [1, -5, -2]);
+
+        let mut integers = arr2(&[[2i64, 1, 0], [0, 3, 1], [1, 0, 4]]);
+        // det(xI - A) = x^3 - 9x^2 + 26x - 25
+        assert_eq!(integers.compute_charpoly_coeffs(), [1, -9, 26, -25]);
+
+        let rational = |n: i64, d: i64| BigRational::new(n.into(), d.into());
+        let mut rationals = arr2(&[
+            [rational(1, 2), rational(1, 1)],
+            [rational(3, 1), rational(2, 1)],
+        ]);
+        assert_eq!(
+            rationals.compute_charpoly_coeffs(),
+            [rational(1, 1), rational(-5, 2), rational(-2, 1)]
+        );
+    }
+
+    #[test]
+    fn charpoly_over_integers_mod_m() {
+        let mut rng = StdRng::seed_from_u64(12);
+        let mut integers = Array2::from_shape_fn((7, 7), |_| rng.gen_range(-20i64, 20));
+        let mut residues = integers.mapv(|value| Mod12(value.rem_euclid(12)));
+        let expected: Vec<Mod12> = integers
+            .compute_charpoly_coeffs()
+            .into_iter()
+            .map(|coeff| Mod12(coeff.rem_euclid(12)))
+            .collect();
+        assert_eq!(residues.compute_charpoly_coeffs(), expected);
+
+        // 2 and 6 are zero divisors, det(xI - A) = x^2 - 8x + 0
+        let mut residues = arr2(&[[Mod12(2), Mod12(6)], [Mod12(2), Mod12(6)]]);
+        assert_eq!(
+            residues.compute_charpoly_coeffs(),
+            [Mod12(1), Mod12(4), Mod12(0)]
+        );
+    }
+
+    #[test]
+    fn generator_minpoly_matches_charpoly() {
+        let mut rng = Xoroshiro64Star::new(1, 2);
+        let minpoly =
+            generator_minpoly_coeffs(&mut rng, 64, |rng| GF2Int::new((rng.seed_0 & 1) as u8));
+        let mut coeffs = minpoly;
+        coeffs.reverse();
+        assert_eq!(
+            crate::gf2vec::GF2Vec64::new(coeffs),
+            charpoly::<Xoroshiro64Star, 2>()
+        );
+
+        let matrix_minpoly = Xoroshiro64Star::matrix().compute_minpoly_coeffs();
+        let mut coeffs = matrix_minpoly;
+        coeffs.reverse();
+        assert_eq!(
+            crate::gf2vec::GF2Vec64::new(coeffs),
+            charpoly::<Xoroshiro64Star, 2>()
+        );
     }
-    let (mut submat, toeplitz) = berkowitz_toeplitz_matrix(matrix);
-    toeplitz.dot(&berkowitz_vector(&mut submat))
 }
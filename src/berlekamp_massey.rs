@@ -0,0 +1,155 @@
+use crate::{gf2int::GF2Int, gf2vec::GF2Vec, rng::RNG};
+
+// 64 bits of ``words`` starting at bit ``start``, zero past the end
+fn packed_window(words: &[u64], start: usize) -> u64 {
+    let (word, bit) = (start / 64, start % 64);
+    let low = words.get(word).copied().unwrap_or(0) >> bit;
+    if bit == 0 {
+        low
+    } else {
+        low | words.get(word + 1).copied().unwrap_or(0) << (64 - bit)
+    }
+}
+
+// connection polynomial 1 + c_1 x + ... + c_L x^L of the shortest lfsr
+// generating ``sequence`` along with the linear complexity after each term,
+// read in order the coefficients are also those of the minimal polynomial
+// x^L + c_1 x^(L - 1) + ... + c_L
+//
+// polynomials and the sequence are packed 64 bits to a word, the sequence
+// reversed so that s_n, s_(n - 1), ..., s_(n - L) are consecutive bits and the
+// discrepancy is a word-wise parity
+fn berlekamp_massey_profile(sequence: &[GF2Int]) -> (Vec<GF2Int>, Vec<usize>) {
+    let total = sequence.len();
+    let mut reversed = vec![0u64; total.div_ceil(64)];
+    for (k, bit) in sequence.iter().rev().enumerate() {
+        reversed[k / 64] |= (bit.val as u64) << (k % 64);
+    }
+    let words = total / 64 + 2;
+    let mut connection = vec![0u64; words];
+    connection[0] = 1;
+    let mut previous = connection.clone();
+    let mut length = 0;
+    let mut shift = 1;
+    let mut profile = Vec::with_capacity(total);
+    for n in 0..total {
+        let start = total - 1 - n;
+        let mut parity = 0;
+        for (w, coeffs) in connection.iter().enumerate().take(length / 64 + 1) {
+            parity ^= coeffs & packed_window(&reversed, start + 64 * w);
+        }
+        if parity.count_ones() % 2 == 0 {
+            shift += 1;
+            profile.push(length);
+            continue;
+        }
+        let last_connection = (2 * length <= n).then(|| connection.clone());
+        // connection += previous * x ** shift
+        let (word_shift, bit_shift) = (shift / 64, shift % 64);
+        for (i, coeffs) in previous.iter().enumerate() {
+            if *coeffs == 0 || i + word_shift >= words {
+                continue;
+            }
+            connection[i + word_shift] ^= coeffs << bit_shift;
+            if bit_shift != 0 && i + word_shift + 1 < words {
+                connection[i + word_shift + 1] ^= coeffs >> (64 - bit_shift);
+            }
+        }
+        if let Some(last_connection) = last_connection {
+            length = n + 1 - length;
+            previous = last_connection;
+            shift = 1;
+        } else {
+            shift += 1;
+        }
+        profile.push(length);
+    }
+    let coeffs = (0..=length)
+        .map(|i| GF2Int::new(((connection[i / 64] >> (i % 64)) & 1) as u8))
+        .collect();
+    (coeffs, profile)
+}
+
+// minimal polynomial coefficients, leading coefficient first as returned by
+// ``compute_charpoly_coeffs``
+pub fn berlekamp_massey(sequence: &[GF2Int]) -> Vec<GF2Int> {
+    berlekamp_massey_profile(sequence).0
+}
+
+// linear complexity of every prefix of ``sequence``
+pub fn linear_complexity_profile(sequence: &[GF2Int]) -> Vec<usize> {
+    berlekamp_massey_profile(sequence).1
+}
+
+pub fn minimal_polynomial<const LIMBS: usize>(sequence: &[GF2Int]) -> GF2Vec<LIMBS> {
+    let mut coeffs = berlekamp_massey(sequence);
+    coeffs.reverse();
+    GF2Vec::new(coeffs)
+}
+
+// ``length`` bits from ``output_bit`` starting at the current state of ``rng``,
+// 2n bits are enough to recover a minimal polynomial of degree n
+pub fn output_sequence<R: RNG>(
+    rng: &mut R,
+    length: usize,
+    output_bit: impl Fn(&mut R) -> GF2Int,
+) -> Vec<GF2Int> {
+    (0..length)
+        .map(|_| {
+            let bit = output_bit(rng);
+            rng.next_state();
+            bit
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gf2vec::GF2Vec64;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn bits(values: &[u8]) -> Vec<GF2Int> {
+        values.iter().map(|value| GF2Int::new(*value)).collect()
+    }
+
+    #[test]
+    fn recovers_a_known_lfsr() {
+        // s_n = s_(n - 3) + s_(n - 4), minimal polynomial x^4 + x + 1
+        let mut sequence = vec![1, 0, 0, 0];
+        for n in 4..30 {
+            sequence.push(sequence[n - 3] ^ sequence[n - 4]);
+        }
+        let minpoly: GF2Vec64 = minimal_polynomial(&bits(&sequence));
+        assert_eq!(minpoly, GF2Vec64::from(0b10011u64));
+        assert_eq!(
+            linear_complexity_profile(&bits(&[0, 0, 0, 1])),
+            [0, 0, 0, 4]
+        );
+        assert_eq!(
+            linear_complexity_profile(&bits(&[1, 1, 1, 1])),
+            [1, 1, 1, 1]
+        );
+    }
+
+    #[test]
+    fn connection_generates_the_sequence() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for length in [1, 63, 64, 65, 200] {
+            let sequence: Vec<GF2Int> = (0..length)
+                .map(|_| GF2Int::new(rng.gen::<u8>() & 1))
+                .collect();
+            let connection = berlekamp_massey(&sequence);
+            let l = connection.len() - 1;
+            assert_eq!(connection[0].val, 1);
+            for n in l..length {
+                let sum = (1..=l).fold(sequence[n].val, |acc, i| {
+                    acc ^ (connection[i].val & sequence[n - i].val)
+                });
+                assert_eq!(sum, 0);
+            }
+            // random sequences have linear complexity about half their length
+            assert!(l.abs_diff(length / 2) <= 8);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::error::RngError;
+use serde::Serialize;
+use std::ops::RangeInclusive;
+
+// calendar date and time as kept by the ds clock, years 2000 to 2099
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+pub const YEARS: RangeInclusive<u16> = 2000..=2099;
+
+pub fn check_year(year: u16) -> Result<(), RngError> {
+    if YEARS.contains(&year) {
+        Ok(())
+    } else {
+        Err(RngError::DateOutOfRange)
+    }
+}
+
+pub fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+pub fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl DateTime {
+    pub fn check(&self) -> Result<(), RngError> {
+        check_year(self.year)?;
+        if (1..=12).contains(&self.month)
+            && (1..=days_in_month(self.year, self.month)).contains(&self.day)
+            && self.hour < 24
+            && self.minute < 60
+            && self.second < 60
+        {
+            Ok(())
+        } else {
+            Err(RngError::DateOutOfRange)
+        }
+    }
+
+    // 0 is sunday, 2000-01-01 was a saturday
+    pub fn weekday(&self) -> u8 {
+        let mut days = (2000..self.year)
+            .map(|year| if is_leap_year(year) { 366 } else { 365 })
+            .sum::<u32>();
+        days += (1..self.month)
+            .map(|month| days_in_month(self.year, month) as u32)
+            .sum::<u32>();
+        days += self.day as u32 - 1;
+        ((days + 6) % 7) as u8
+    }
+
+    // every (month, day) of ``year``
+    pub fn dates(year: u16) -> impl Iterator<Item = (u8, u8)> {
+        (1..=12)
+            .flat_map(move |month| (1..=days_in_month(year, month)).map(move |day| (month, day)))
+    }
+}
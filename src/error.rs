@@ -0,0 +1,61 @@
+use crate::{modarith::ModArithError, progress::Cancelled};
+use std::fmt;
+
+// errors of the fallible ``try_`` variants, for callers that cannot afford
+// the panics of the plain ones on bad input
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RngError {
+    // degree, division or reduction by the zero polynomial
+    ZeroPolynomial,
+    DivisionByZero,
+    // polynomial without inverse modulo the modulus or singular matrix
+    NotInvertible,
+    // all-zero generator state, which never leaves itself
+    ZeroState,
+    // the target is not a power of the base, e.g. a state off the orbit
+    LogNotFound,
+    // discrete log group with a prime factor too large for baby-step
+    // giant-step
+    TooLarge,
+    // z ** group_order is not 1, so the order given is not a multiple of
+    // the multiplicative order
+    NotGroupOrder,
+    Cancelled,
+    // date or time the ds clock cannot show, years running from 2000 to 2099
+    DateOutOfRange,
+    ModArith(ModArithError),
+    // malformed polynomial, state or matrix text
+    Parse(String),
+}
+
+impl fmt::Display for RngError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RngError::ZeroPolynomial => write!(f, "zero polynomial"),
+            RngError::DivisionByZero => write!(f, "division by zero"),
+            RngError::NotInvertible => write!(f, "not invertible"),
+            RngError::ZeroState => write!(f, "all-zero state"),
+            RngError::LogNotFound => write!(f, "remainder not found"),
+            RngError::TooLarge => write!(f, "group order too large"),
+            RngError::NotGroupOrder => write!(f, "z ** group_order is not 1"),
+            RngError::Cancelled => write!(f, "cancelled"),
+            RngError::DateOutOfRange => write!(f, "date and time outside 2000 to 2099"),
+            RngError::ModArith(error) => write!(f, "{error}"),
+            RngError::Parse(message) => write!(f, "parse error: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for RngError {}
+
+impl From<Cancelled> for RngError {
+    fn from(_: Cancelled) -> Self {
+        RngError::Cancelled
+    }
+}
+
+impl From<ModArithError> for RngError {
+    fn from(error: ModArithError) -> Self {
+        RngError::ModArith(error)
+    }
+}
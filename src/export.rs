@@ -0,0 +1,253 @@
+use crate::{
+    gen3, gen4,
+    gf2int::GF2Int,
+    gf2vec::GF2Vec,
+    notation::format_row,
+    pokemon::{Gender, Pokemon, ShinyType},
+    sv::tera_raid::TeraRaid,
+};
+use ndarray::Array2;
+use serde::{ser::SerializeSeq, Serialize, Serializer};
+use std::{
+    fmt::{Display, UpperHex},
+    io::Write,
+};
+
+// seeds and states are written as hex strings, json numbers lose precision
+// past 2 ** 53 in javascript; upper case like the state notation
+pub fn serialize_hex<S: Serializer, T: UpperHex>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{:#X}", value))
+}
+
+// advances as decimal strings for the same reason, as they go up to 2 ** 128
+pub fn serialize_decimal<S: Serializer, T: Display>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&value.to_string())
+}
+
+pub fn serialize_hex_array<S: Serializer, T: UpperHex>(
+    values: &[T],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(Some(values.len()))?;
+    for value in values {
+        seq.serialize_element(&format!("{:#X}", value))?;
+    }
+    seq.end()
+}
+
+// polynomials as the hex value of their coefficients, bit i being x ** i
+impl<const LIMBS: usize> Serialize for GF2Vec<LIMBS> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:#X}", self))
+    }
+}
+
+// matrices as their rows of 0/1 characters
+#[derive(Clone, Debug, Serialize)]
+pub struct MatrixRecord {
+    pub rows: usize,
+    pub columns: usize,
+    pub data: Vec<String>,
+}
+
+impl MatrixRecord {
+    pub fn new(matrix: &Array2<GF2Int>) -> MatrixRecord {
+        MatrixRecord {
+            rows: matrix.shape()[0],
+            columns: matrix.shape()[1],
+            data: matrix.rows().into_iter().map(format_row).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DistanceRecord<R: Serialize> {
+    pub origin: R,
+    pub target: R,
+    // decimal string as distances go up to 2 ** 128
+    pub distance: String,
+}
+
+impl<R: Serialize> DistanceRecord<R> {
+    pub fn new(origin: R, target: R, distance: u128) -> DistanceRecord<R> {
+        DistanceRecord {
+            origin,
+            target,
+            distance: distance.to_string(),
+        }
+    }
+}
+
+// flat row of a search result for csv, which cannot nest the ivs, with the
+// result's own fields after the pokemon's
+pub trait ResultRow {
+    type Row: Serialize;
+
+    fn row(&self, advance: u128) -> Self::Row;
+}
+
+macro_rules! pokemon_row {
+    ($row:ident for $result:ty $(, $extra:ident: $extra_ty:ty)*) => {
+        #[derive(Clone, Debug, Serialize)]
+        pub struct $row {
+            #[serde(serialize_with = "serialize_decimal")]
+            pub advance: u128,
+            #[serde(serialize_with = "serialize_hex")]
+            pub ec: u32,
+            #[serde(serialize_with = "serialize_hex")]
+            pub pid: u32,
+            pub shiny: ShinyType,
+            pub hp: u8,
+            pub atk: u8,
+            pub def: u8,
+            pub spa: u8,
+            pub spd: u8,
+            pub spe: u8,
+            pub ability: u8,
+            pub gender: Gender,
+            pub nature: u8,
+            $(pub $extra: $extra_ty,)*
+        }
+
+        impl ResultRow for $result {
+            type Row = $row;
+
+            fn row(&self, advance: u128) -> $row {
+                let pokemon: &Pokemon = self.as_ref();
+                let [hp, atk, def, spa, spd, spe] = pokemon.ivs;
+                $row {
+                    advance,
+                    ec: pokemon.ec,
+                    pid: pokemon.pid,
+                    shiny: pokemon.shiny,
+                    hp,
+                    atk,
+                    def,
+                    spa,
+                    spd,
+                    spe,
+                    ability: pokemon.ability,
+                    gender: pokemon.gender,
+                    nature: pokemon.nature,
+                    $($extra: self.$extra,)*
+                }
+            }
+        }
+    };
+}
+
+pokemon_row!(PokemonRow for Pokemon);
+pokemon_row!(Gen3WildRow for gen3::Wild, slot_rand: u16, level_rand: u16);
+pokemon_row!(Gen4WildRow for gen4::Wild, slot: u8);
+pokemon_row!(TeraRaidRow for TeraRaid, height: u8, weight: u8, scale: u8, tera_type: u8);
+
+// rows of the (advance, result) pairs returned by ``search::search``
+pub fn result_rows<T: ResultRow>(results: &[(u128, T)]) -> Vec<T::Row> {
+    results
+        .iter()
+        .map(|(advance, result)| result.row(*advance))
+        .collect()
+}
+
+pub fn write_json<W: Write, T: Serialize + ?Sized>(writer: W, value: &T) -> serde_json::Result<()> {
+    serde_json::to_writer_pretty(writer, value)
+}
+
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(value)
+}
+
+// one header row from the field names followed by one row per record
+pub fn write_csv<W: Write, T: Serialize>(
+    writer: W,
+    records: impl IntoIterator<Item = T>,
+) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for record in records {
+        writer.serialize(record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mt::Mt19937, rng::RNG};
+
+    #[test]
+    fn advances_are_written_as_decimal_strings() {
+        let pokemon = Pokemon {
+            ec: 0x12345678,
+            pid: 0x9ABCDEF0,
+            shiny: ShinyType::None,
+            ivs: [31, 30, 29, 28, 27, 26],
+            ability: 1,
+            gender: Gender::Female,
+            nature: 3,
+        };
+        let rows = result_rows(&[(u128::MAX, pokemon)]);
+        let json = to_json(&rows).unwrap();
+        assert!(json.contains(r#""advance": "340282366920938463463374607431768211455""#));
+        assert!(json.contains(r#""ec": "0x12345678""#));
+        assert!(json.contains(r#""pid": "0x9ABCDEF0""#));
+
+        let mut csv = vec![];
+        write_csv(&mut csv, &rows).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(
+            csv.lines().nth(1),
+            Some("340282366920938463463374607431768211455,0x12345678,0x9ABCDEF0,None,31,30,29,28,27,26,1,Female,3")
+        );
+    }
+
+    #[test]
+    fn rows_keep_the_result_fields() {
+        let raid = TeraRaid {
+            pokemon: Pokemon {
+                ec: 0xABCD,
+                pid: 0x1234,
+                shiny: ShinyType::Star,
+                ivs: [31; 6],
+                ability: 2,
+                gender: Gender::Genderless,
+                nature: 24,
+            },
+            height: 1,
+            weight: 2,
+            scale: 255,
+            tera_type: 17,
+        };
+        let mut csv = vec![];
+        write_csv(&mut csv, result_rows(&[(7, raid)])).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("advance,ec,pid,shiny,hp,atk,def,spa,spd,spe,ability,gender,nature,height,weight,scale,tera_type")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("7,0xABCD,0x1234,Star,31,31,31,31,31,31,2,Genderless,24,1,2,255,17")
+        );
+    }
+
+    #[test]
+    fn mt_states_export_oldest_word_first() {
+        let mut rng = Mt19937::new(5489);
+        let words = rng.words();
+        let json: serde_json::Value = serde_json::from_str(&to_json(&rng).unwrap()).unwrap();
+        assert_eq!(json["state"].as_array().unwrap().len(), 624);
+        assert_eq!(json["state"][0], "0x1571");
+
+        rng.next_state();
+        let json: serde_json::Value = serde_json::from_str(&to_json(&rng).unwrap()).unwrap();
+        assert_eq!(json["state"][0], format!("{:#X}", words[1]));
+    }
+}
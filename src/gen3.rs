@@ -0,0 +1,211 @@
+use crate::{
+    lcg::PokeRng,
+    pokemon::{legacy_pokemon, Pokemon},
+};
+use serde::Serialize;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Method {
+    One,
+    Two,
+    Four,
+}
+
+impl Method {
+    // calls skipped before the first and between the two iv calls
+    fn skips(self) -> (u32, u32) {
+        match self {
+            Method::One => (0, 0),
+            Method::Two => (1, 0),
+            Method::Four => (0, 1),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Wild {
+    pub slot_rand: u16,
+    pub level_rand: u16,
+    pub pokemon: Pokemon,
+}
+
+impl AsRef<Pokemon> for Wild {
+    fn as_ref(&self) -> &Pokemon {
+        &self.pokemon
+    }
+}
+
+// hp/atk/def then spe/spa/spd packed 5 bits each, stored hp/atk/def/spa/spd/spe
+pub fn unpack_ivs(iv1: u16, iv2: u16) -> [u8; 6] {
+    let iv = |call: u16, i: u16| ((call >> (5 * i)) & 31) as u8;
+    [
+        iv(iv1, 0),
+        iv(iv1, 1),
+        iv(iv1, 2),
+        iv(iv2, 1),
+        iv(iv2, 2),
+        iv(iv2, 0),
+    ]
+}
+
+fn pack_ivs(ivs: [u8; 6]) -> (u16, u16) {
+    let [hp, atk, def, spa, spd, spe] = ivs.map(|iv| iv as u16);
+    (hp | atk << 5 | def << 10, spe | spa << 5 | spd << 10)
+}
+
+fn roll_ivs(rng: &mut PokeRng, method: Method) -> [u8; 6] {
+    let (before, between) = method.skips();
+    rng.advance(before);
+    let iv1 = rng.next_u16();
+    rng.advance(between);
+    unpack_ivs(iv1, rng.next_u16())
+}
+
+pub fn roll_pid(rng: &mut PokeRng) -> u32 {
+    let low = rng.next_u16() as u32;
+    ((rng.next_u16() as u32) << 16) | low
+}
+
+// pid then ivs straight from the seed
+pub fn generate_static(seed: u32, method: Method, tid: u16, sid: u16, gender_ratio: u8) -> Pokemon {
+    let mut rng = PokeRng::new(seed);
+    let pid = roll_pid(&mut rng);
+    let ivs = roll_ivs(&mut rng, method);
+    legacy_pokemon(pid, ivs, ((sid as u32) << 16) | tid as u32, gender_ratio)
+}
+
+// H-1/H-2/H-4: slot, level and nature, then pids are rerolled until they
+// match the nature
+pub fn generate_wild(seed: u32, method: Method, tid: u16, sid: u16, gender_ratio: u8) -> Wild {
+    let mut rng = PokeRng::new(seed);
+    let slot_rand = rng.next_u16() % 100;
+    let level_rand = rng.next_u16();
+    let nature = (rng.next_u16() % 25) as u32;
+    let mut pid = roll_pid(&mut rng);
+    while pid % 25 != nature {
+        pid = roll_pid(&mut rng);
+    }
+    let ivs = roll_ivs(&mut rng, method);
+    Wild {
+        slot_rand,
+        level_rand,
+        pokemon: legacy_pokemon(pid, ivs, ((sid as u32) << 16) | tid as u32, gender_ratio),
+    }
+}
+
+// seeds returned by the first iv call, the low 16 bits of the seed and the
+// unused top bit of the call are the only unknowns so this takes 2 ** 17 steps
+fn iv_seeds(ivs: [u8; 6], method: Method) -> Vec<u32> {
+    let (iv1, iv2) = pack_ivs(ivs);
+    let (_, between) = method.skips();
+    let mut seeds = vec![];
+    for high in 0..2u32 {
+        let top = ((high << 15) | iv1 as u32) << 16;
+        for low in 0..0x10000 {
+            let mut rng = PokeRng::new(top | low);
+            rng.advance(between);
+            if rng.next_u16() & 0x7FFF == iv2 {
+                seeds.push(top | low);
+            }
+        }
+    }
+    seeds
+}
+
+// static seeds generating ``ivs``, optionally restricted to a nature
+pub fn seeds_from_ivs(ivs: [u8; 6], method: Method, nature: Option<u8>) -> Vec<u32> {
+    let (before, _) = method.skips();
+    let mut seeds: Vec<u32> = iv_seeds(ivs, method)
+        .into_iter()
+        .map(|iv_seed| {
+            // back past the iv call, the skips and both pid calls
+            let mut rng = PokeRng::new(iv_seed);
+            rng.reverse(3 + before);
+            rng.seed
+        })
+        .filter(|&seed| {
+            nature.is_none_or(|nature| generate_static(seed, method, 0, 0, 0).nature == nature)
+        })
+        .collect();
+    seeds.sort_unstable();
+    seeds
+}
+
+// wild seeds generating ``ivs``, every pid reroll before the final pid is
+// walked back as long as it would not have matched the nature
+pub fn wild_seeds_from_ivs(ivs: [u8; 6], method: Method, nature: Option<u8>) -> Vec<u32> {
+    let (before, _) = method.skips();
+    let mut seeds = vec![];
+    for iv_seed in iv_seeds(ivs, method) {
+        let mut rng = PokeRng::new(iv_seed);
+        rng.reverse(1 + before);
+        let high = rng.previous_u16() as u32;
+        let pid = (high << 16) | rng.previous_u16() as u32;
+        let pid_nature = pid % 25;
+        if nature.is_some_and(|nature| nature as u32 != pid_nature) {
+            continue;
+        }
+        loop {
+            // the call before the pid as the nature call
+            let call = rng.previous_u16() as u32;
+            if call % 25 == pid_nature {
+                let mut origin = rng;
+                origin.reverse(2);
+                seeds.push(origin.seed);
+            }
+            // otherwise as the high half of a rejected pid
+            let rejected = (call << 16) | rng.previous_u16() as u32;
+            if rejected % 25 == pid_nature {
+                break;
+            }
+        }
+    }
+    seeds.sort_unstable();
+    seeds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // seed 0, the seed of ruby/sapphire with a dead battery
+    const SEED_0_IVS: [u8; 6] = [17, 19, 20, 13, 12, 16];
+
+    #[test]
+    fn seed_0_generates_the_known_pokemon() {
+        let pokemon = generate_static(0, Method::One, 0, 0, 0x7F);
+        assert_eq!(pokemon.pid, 0xE97E0000);
+        assert_eq!(pokemon.nature, 14);
+        assert_eq!(pokemon.ivs, SEED_0_IVS);
+        assert_eq!(
+            generate_static(0, Method::Two, 0, 0, 0x7F).ivs,
+            [16, 13, 12, 18, 3, 2]
+        );
+        assert_eq!(
+            generate_static(0, Method::Four, 0, 0, 0x7F).ivs,
+            [17, 19, 20, 18, 3, 2]
+        );
+    }
+
+    #[test]
+    fn seeds_are_recovered_from_ivs() {
+        assert!(seeds_from_ivs(SEED_0_IVS, Method::One, None).contains(&0));
+        assert!(seeds_from_ivs(SEED_0_IVS, Method::One, Some(14)).contains(&0));
+        assert!(!seeds_from_ivs(SEED_0_IVS, Method::One, Some(13)).contains(&0));
+        for method in [Method::One, Method::Two, Method::Four] {
+            let pokemon = generate_static(0xDEADBEEF, method, 0, 0, 0x7F);
+            let seeds = seeds_from_ivs(pokemon.ivs, method, Some(pokemon.nature));
+            assert!(seeds.contains(&0xDEADBEEF));
+            assert!(seeds
+                .iter()
+                .all(|&seed| generate_static(seed, method, 0, 0, 0x7F).ivs == pokemon.ivs));
+
+            let wild = generate_wild(0xDEADBEEF, method, 0, 0, 0x7F);
+            let seeds = wild_seeds_from_ivs(wild.pokemon.ivs, method, Some(wild.pokemon.nature));
+            assert!(seeds.contains(&0xDEADBEEF));
+            assert!(seeds.iter().all(
+                |&seed| generate_wild(seed, method, 0, 0, 0x7F).pokemon.ivs == wild.pokemon.ivs
+            ));
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use crate::{
+    datetime::{check_year, DateTime},
+    error::RngError,
+    gen3::{roll_pid, unpack_ivs},
+    lcg::PokeRng,
+    pokemon::{legacy_pokemon, Pokemon},
+};
+use serde::Serialize;
+
+// method j is diamond/pearl/platinum, method k heartgold/soulsilver
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Method {
+    J,
+    K,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Lead {
+    None,
+    Synchronize(u8),
+    // pid offset of the gender opposite to the lead, e.g. 0 for a female
+    // target or 25 * (gender_ratio / 25 + 1) for a male one
+    CuteCharm(u32),
+}
+
+impl Method {
+    fn slot(self, call: u16) -> u8 {
+        match self {
+            Method::J => (call / 656) as u8,
+            Method::K => (call % 100) as u8,
+        }
+    }
+
+    fn nature(self, call: u16) -> u32 {
+        match self {
+            Method::J => (call / 0xA3E) as u32,
+            Method::K => (call % 25) as u32,
+        }
+    }
+
+    fn synchronizes(self, call: u16) -> bool {
+        match self {
+            Method::J => call >> 15 == 0,
+            Method::K => call.is_multiple_of(2),
+        }
+    }
+
+    fn cute_charms(self, call: u16) -> bool {
+        match self {
+            Method::J => call / 0x5556 != 0,
+            Method::K => !call.is_multiple_of(3),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Wild {
+    pub slot: u8,
+    pub pokemon: Pokemon,
+}
+
+impl AsRef<Pokemon> for Wild {
+    fn as_ref(&self) -> &Pokemon {
+        &self.pokemon
+    }
+}
+
+// grass encounter from the seed before the slot call, levels being fixed
+// per slot
+pub fn generate_wild(
+    seed: u32,
+    method: Method,
+    lead: Lead,
+    tid: u16,
+    sid: u16,
+    gender_ratio: u8,
+) -> Wild {
+    let mut rng = PokeRng::new(seed);
+    let slot = method.slot(rng.next_u16());
+    let pid = match lead {
+        Lead::Synchronize(nature) if method.synchronizes(rng.next_u16()) => {
+            roll_nature_pid(&mut rng, nature as u32)
+        }
+        Lead::CuteCharm(offset) if method.cute_charms(rng.next_u16()) => {
+            offset + method.nature(rng.next_u16())
+        }
+        _ => {
+            let nature = method.nature(rng.next_u16());
+            roll_nature_pid(&mut rng, nature)
+        }
+    };
+    let iv1 = rng.next_u16();
+    let ivs = unpack_ivs(iv1, rng.next_u16());
+    Wild {
+        slot,
+        pokemon: legacy_pokemon(pid, ivs, ((sid as u32) << 16) | tid as u32, gender_ratio),
+    }
+}
+
+fn roll_nature_pid(rng: &mut PokeRng, nature: u32) -> u32 {
+    loop {
+        let pid = roll_pid(rng);
+        if pid % 25 == nature {
+            return pid;
+        }
+    }
+}
+
+// AB CD EFGH: AB = month * day + minute + second, CD = hour and
+// EFGH = year - 2000 + delay, added on so that long delays carry into CD
+pub fn initial_seed(date_time: &DateTime, delay: u32) -> Result<u32, RngError> {
+    date_time.check()?;
+    let ab = (date_time.month as u32 * date_time.day as u32
+        + date_time.minute as u32
+        + date_time.second as u32)
+        & 0xFF;
+    Ok(((ab << 24) | ((date_time.hour as u32) << 16))
+        .wrapping_add(date_time.year as u32 - 2000)
+        .wrapping_add(delay))
+}
+
+// every (date and time, delay) of ``year`` hitting ``seed`` with delays in
+// ``min_delay..=max_delay``
+pub fn seed_times(
+    seed: u32,
+    year: u16,
+    min_delay: u32,
+    max_delay: u32,
+) -> Result<Vec<(DateTime, u32)>, RngError> {
+    check_year(year)?;
+    let mut times = vec![];
+    for ab in 0..0x100 {
+        for hour in 0..24 {
+            // the delay is whatever is left of the seed once AB CD is taken
+            // off, carries included
+            let delay = seed
+                .wrapping_sub((ab << 24) | (hour << 16))
+                .wrapping_sub(year as u32 - 2000);
+            if delay < min_delay || delay > max_delay {
+                continue;
+            }
+            for (month, day) in DateTime::dates(year) {
+                for minute in 0..60 {
+                    for second in 0..60 {
+                        if (month as u32 * day as u32 + minute + second) & 0xFF == ab {
+                            times.push((
+                                DateTime {
+                                    year,
+                                    month,
+                                    day,
+                                    hour: hour as u8,
+                                    minute: minute as u8,
+                                    second: second as u8,
+                                },
+                                delay,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    times.sort();
+    Ok(times)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date_time(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> DateTime {
+        DateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        }
+    }
+
+    #[test]
+    fn generate_wild_known_answers() {
+        use crate::pokemon::Gender;
+        use Method::{J, K};
+        let none = Lead::None;
+        // adamant
+        let sync = Lead::Synchronize(3);
+        // ratio 127 puts the cute charm offset of a male target at 150
+        let (female, male) = (Lead::CuteCharm(0), Lead::CuteCharm(150));
+        // from an independent python port of pokefinder's WildGenerator4
+        let cases = [
+            (0x200, J, none, 54, 0x1E85805D, [14, 10, 12, 26, 8, 23]),
+            (0x200, K, none, 97, 0xDE1412AF, [25, 19, 28, 16, 27, 17]),
+            // synchronizing, then failing with the nature from the next call
+            (0x101, J, sync, 3, 0x263650AB, [11, 25, 6, 24, 4, 0]),
+            (0x100, K, sync, 66, 0x558A3390, [23, 23, 5, 17, 24, 25]),
+            (0x100, J, sync, 77, 0xD45D0B8B, [22, 15, 31, 2, 19, 9]),
+            (0x101, K, sync, 69, 0x387CC4D7, [2, 15, 20, 20, 31, 9]),
+            // cute charm forcing either gender, then failing
+            (0x100, J, female, 77, 0x14, [15, 1, 14, 28, 11, 10]),
+            (0x100, J, male, 77, 0xAA, [15, 1, 14, 28, 11, 10]),
+            (0x100, K, female, 66, 0x12, [15, 1, 14, 28, 11, 10]),
+            (0x102, K, male, 7, 0x8A0DE9C4, [28, 18, 3, 24, 25, 10]),
+        ];
+        for (seed, method, lead, slot, pid, ivs) in cases {
+            let wild = generate_wild(seed, method, lead, 0, 0, 127);
+            assert_eq!(
+                (wild.slot, wild.pokemon.pid, wild.pokemon.ivs),
+                (slot, pid, ivs),
+                "{seed:#x} {method:?} {lead:?}"
+            );
+        }
+        let pokemon = |seed, method, lead| generate_wild(seed, method, lead, 0, 0, 127).pokemon;
+        assert_eq!(pokemon(0x101, J, sync).nature, 3);
+        assert_eq!(pokemon(0x100, J, female).gender, Gender::Female);
+        assert_eq!(pokemon(0x100, J, male).gender, Gender::Male);
+        assert_eq!(pokemon(0x100, K, female).nature, 18);
+    }
+
+    #[test]
+    fn initial_seed_matches_known_pairs() {
+        // method j, platinum at 2009-03-14 12:34:56 with delay 600
+        assert_eq!(
+            initial_seed(&date_time(2009, 3, 14, 12, 34, 56), 600),
+            Ok(0x840C0261)
+        );
+        // method k, heartgold at 2010-01-01 23:59:59 with delay 65530, the
+        // delay carrying into the hour byte
+        assert_eq!(
+            initial_seed(&date_time(2010, 1, 1, 23, 59, 59), 65530),
+            Ok(0x77180004)
+        );
+        // ab wrapping through 0x100 and the seed through 2 ** 32
+        assert_eq!(
+            initial_seed(&date_time(2000, 12, 31, 23, 59, 59), 0x15E90000),
+            Ok(0x00000000)
+        );
+        // the ds clock stops at 2099, and year - 2000 would underflow
+        for date_time in [
+            date_time(1999, 12, 31, 23, 59, 59),
+            date_time(2100, 1, 1, 0, 0, 0),
+            date_time(2009, 2, 29, 0, 0, 0),
+            date_time(2009, 3, 14, 24, 0, 0),
+        ] {
+            assert_eq!(initial_seed(&date_time, 600), Err(RngError::DateOutOfRange));
+        }
+    }
+
+    #[test]
+    fn seed_times_inverts_initial_seed() {
+        for (date_time, delay) in [
+            (date_time(2009, 3, 14, 12, 34, 56), 600),
+            (date_time(2010, 1, 1, 23, 59, 59), 65530),
+        ] {
+            let seed = initial_seed(&date_time, delay).unwrap();
+            let times = seed_times(seed, date_time.year, 0, 100000).unwrap();
+            assert!(times.contains(&(date_time, delay)));
+            assert!(times
+                .iter()
+                .all(|(time, delay)| initial_seed(time, *delay) == Ok(seed)));
+        }
+        // the delay is the full difference, not one taken mod 2 ** 16
+        let times = seed_times(0x77180004, 2010, 0, 65529).unwrap();
+        assert!(times
+            .iter()
+            .all(|(time, _)| *time != date_time(2010, 1, 1, 23, 59, 59)));
+        assert_eq!(
+            seed_times(0x77180004, 1999, 0, 65529),
+            Err(RngError::DateOutOfRange)
+        );
+    }
+}
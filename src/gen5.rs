@@ -0,0 +1 @@
+pub mod initial_seed;
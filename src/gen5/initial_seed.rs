@@ -0,0 +1,361 @@
+use crate::{
+    datetime::{check_year, DateTime},
+    error::RngError,
+    lcg::BwRng,
+    mt::Mt19937,
+    progress::{ChunkCounter, Progress},
+};
+use rayon::prelude::*;
+use std::ops::RangeInclusive;
+
+// the five nazo words of each version follow from the first
+pub const BLACK_JPN: u32 = 0x02215F10;
+pub const WHITE_JPN: u32 = 0x02215F30;
+pub const BLACK_ENG: u32 = 0x022160B0;
+pub const WHITE_ENG: u32 = 0x022160D0;
+
+pub fn nazo(base: u32) -> [u32; 5] {
+    [base, base + 0xFC, base + 0xFC, base + 0x148, base + 0x148]
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Profile {
+    pub nazo: [u32; 5],
+    pub mac: u64,
+    pub vcount: u8,
+    pub vframe: u8,
+    pub gxstat: u32,
+}
+
+// keypress bits, pressed keys are cleared from 0x2FFF in the message
+pub const KEY_A: u16 = 0x1;
+pub const KEY_B: u16 = 0x2;
+pub const KEY_SELECT: u16 = 0x4;
+pub const KEY_START: u16 = 0x8;
+pub const KEY_RIGHT: u16 = 0x10;
+pub const KEY_LEFT: u16 = 0x20;
+pub const KEY_UP: u16 = 0x40;
+pub const KEY_DOWN: u16 = 0x80;
+pub const KEY_R: u16 = 0x100;
+pub const KEY_L: u16 = 0x200;
+pub const KEY_X: u16 = 0x400;
+pub const KEY_Y: u16 = 0x800;
+
+fn bcd(value: u8) -> u32 {
+    (((value / 10) << 4) | (value % 10)) as u32
+}
+
+// sha-1 message of the 52 bytes the game hashes, already padded
+pub fn message(
+    profile: &Profile,
+    timer0: u16,
+    date_time: &DateTime,
+    keys: u16,
+) -> Result<[u32; 16], RngError> {
+    date_time.check()?;
+    Ok(pack_message(profile, timer0, date_time, keys))
+}
+
+// ``message`` for a checked date and time
+fn pack_message(profile: &Profile, timer0: u16, date_time: &DateTime, keys: u16) -> [u32; 16] {
+    let mut message = [0; 16];
+    for (i, nazo) in profile.nazo.iter().enumerate() {
+        message[i] = nazo.swap_bytes();
+    }
+    message[5] = (((profile.vcount as u32) << 16) | timer0 as u32).swap_bytes();
+    message[6] = (profile.mac & 0xFFFF) as u32;
+    message[7] = ((profile.mac >> 16) as u32) ^ ((profile.vframe as u32) << 24) ^ profile.gxstat;
+    message[8] = (bcd((date_time.year - 2000) as u8) << 24)
+        | (bcd(date_time.month) << 16)
+        | (bcd(date_time.day) << 8)
+        | date_time.weekday() as u32;
+    // hours past noon are flagged
+    let pm = if date_time.hour >= 12 { 0x40 } else { 0 };
+    message[9] = ((bcd(date_time.hour) | pm) << 24)
+        | (bcd(date_time.minute) << 16)
+        | (bcd(date_time.second) << 8);
+    message[12] = ((0x2FFF ^ keys) as u32).swap_bytes();
+    message[13] = 0x80000000;
+    message[15] = 0x1A0;
+    message
+}
+
+// sha-1 of a single padded block
+fn sha1(message: &[u32; 16]) -> [u32; 5] {
+    const INITIAL: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut w = [0u32; 80];
+    w[..16].copy_from_slice(message);
+    for i in 16..80 {
+        w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e] = INITIAL;
+    for (i, w) in w.iter().enumerate() {
+        let (f, k) = match i {
+            0..=19 => ((b & c) | (!b & d), 0x5A827999),
+            20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+            40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+            _ => (b ^ c ^ d, 0xCA62C1D6),
+        };
+        let temp = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(k)
+            .wrapping_add(*w);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = temp;
+    }
+    let mut hash = INITIAL;
+    for (h, value) in hash.iter_mut().zip([a, b, c, d, e]) {
+        *h = h.wrapping_add(value);
+    }
+    hash
+}
+
+// first two hash words as a little endian seed, advanced once
+pub fn initial_seed(
+    profile: &Profile,
+    timer0: u16,
+    date_time: &DateTime,
+    keys: u16,
+) -> Result<u64, RngError> {
+    date_time.check()?;
+    Ok(hash_seed(profile, timer0, date_time, keys))
+}
+
+fn hash_seed(profile: &Profile, timer0: u16, date_time: &DateTime, keys: u16) -> u64 {
+    let hash = sha1(&pack_message(profile, timer0, date_time, keys));
+    let seed = ((hash[1].swap_bytes() as u64) << 32) | hash[0].swap_bytes() as u64;
+    BwRng::new(seed).next_u64()
+}
+
+// mt19937 seeded by the high half of the seed, ivs are the top 5 bits of
+// each output after ``advance`` skipped ones
+pub fn ivs(seed: u64, advance: usize) -> [u8; 6] {
+    let mut mt = Mt19937::new((seed >> 32) as u32);
+    mt.advance(advance);
+    [(); 6].map(|_| (mt.next_u32() >> 27) as u8)
+}
+
+// high half of the lcg after ``advance`` steps, with the ability bit flipped
+// as wild encounters do
+pub fn wild_pid(seed: u64, advance: u64) -> u32 {
+    let mut rng = BwRng::new(seed);
+    rng.advance(advance);
+    rng.next_u32() ^ 0x10000
+}
+
+pub enum Target {
+    Seed(u64),
+    // ivs between min and max after ``advance`` mt outputs
+    Ivs {
+        min: [u8; 6],
+        max: [u8; 6],
+        advance: usize,
+    },
+}
+
+impl Target {
+    fn matches(&self, seed: u64) -> bool {
+        match self {
+            Target::Seed(target) => seed == *target,
+            Target::Ivs { min, max, advance } => {
+                let ivs = ivs(seed, *advance);
+                (0..6).all(|i| (min[i]..=max[i]).contains(&ivs[i]))
+            }
+        }
+    }
+}
+
+pub struct SearchRange {
+    pub years: RangeInclusive<u16>,
+    pub hours: RangeInclusive<u8>,
+    pub minutes: RangeInclusive<u8>,
+    pub seconds: RangeInclusive<u8>,
+    pub timer0: RangeInclusive<u16>,
+    pub keys: Vec<u16>,
+}
+
+impl SearchRange {
+    // the last hour, minute and second are the largest searched
+    fn check(&self) -> Result<(), RngError> {
+        check_year(*self.years.start())?;
+        check_year(*self.years.end())?;
+        if *self.hours.end() < 24 && *self.minutes.end() < 60 && *self.seconds.end() < 60 {
+            Ok(())
+        } else {
+            Err(RngError::DateOutOfRange)
+        }
+    }
+}
+
+// (date and time, timer0, keys, seed) hitting ``target``, dates in parallel
+pub fn search(
+    profile: &Profile,
+    range: &SearchRange,
+    target: &Target,
+) -> Result<Vec<(DateTime, u16, u16, u64)>, RngError> {
+    search_with_progress(profile, range, target, &Progress::new())
+}
+
+// each date is a chunk
+pub fn search_with_progress(
+    profile: &Profile,
+    range: &SearchRange,
+    target: &Target,
+    progress: &Progress,
+) -> Result<Vec<(DateTime, u16, u16, u64)>, RngError> {
+    range.check()?;
+    let dates: Vec<(u16, u8, u8)> = range
+        .years
+        .clone()
+        .flat_map(|year| DateTime::dates(year).map(move |(month, day)| (year, month, day)))
+        .collect();
+    let counter = ChunkCounter::new(progress, dates.len() as u128);
+    let results: Vec<Vec<(DateTime, u16, u16, u64)>> = dates
+        .into_par_iter()
+        .map(|(year, month, day)| {
+            let mut results = vec![];
+            for hour in range.hours.clone() {
+                progress.check()?;
+                for minute in range.minutes.clone() {
+                    for second in range.seconds.clone() {
+                        let date_time = DateTime {
+                            year,
+                            month,
+                            day,
+                            hour,
+                            minute,
+                            second,
+                        };
+                        for timer0 in range.timer0.clone() {
+                            for &keys in &range.keys {
+                                let seed = hash_seed(profile, timer0, &date_time, keys);
+                                if target.matches(seed) {
+                                    results.push((date_time, timer0, keys, seed));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            counter.finish();
+            Ok(results)
+        })
+        .collect::<Result<_, RngError>>()?;
+    let mut results: Vec<(DateTime, u16, u16, u64)> = results.into_iter().flatten().collect();
+    results.sort_unstable();
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILE: Profile = Profile {
+        nazo: [
+            WHITE_ENG,
+            WHITE_ENG + 0xFC,
+            WHITE_ENG + 0xFC,
+            WHITE_ENG + 0x148,
+            WHITE_ENG + 0x148,
+        ],
+        mac: 0x0009BF123456,
+        vcount: 0x2F,
+        vframe: 5,
+        gxstat: 6,
+    };
+
+    const DATE_TIME: DateTime = DateTime {
+        year: 2011,
+        month: 3,
+        day: 6,
+        hour: 21,
+        minute: 30,
+        second: 15,
+    };
+
+    #[test]
+    fn sha1_matches_the_fips_example() {
+        // "abc" padded to one block
+        let mut block = [0; 16];
+        block[0] = 0x61626380;
+        block[15] = 0x18;
+        assert_eq!(
+            sha1(&block),
+            [0xA9993E36, 0x4706816A, 0xBA3E2571, 0x7850C26C, 0x9CD0D89D]
+        );
+    }
+
+    #[test]
+    fn message_packs_the_ds_parameters() {
+        assert_eq!(nazo(WHITE_ENG), PROFILE.nazo);
+        let message = message(&PROFILE, 0xC7A, &DATE_TIME, KEY_A | KEY_START).unwrap();
+        assert_eq!(message[5], 0x7A0C2F00);
+        assert_eq!(message[6], 0x3456);
+        assert_eq!(message[7], 0x0009BF12 ^ 0x05000000 ^ 6);
+        // 2011-03-06 was a sunday, 21:30 is flagged as pm
+        assert_eq!(message[8], 0x11030600);
+        assert_eq!(message[9], 0x61301500);
+        assert_eq!(message[12], 0xF62F0000);
+        assert_eq!(message[13], 0x80000000);
+        assert_eq!(message[15], 0x1A0);
+    }
+
+    #[test]
+    fn wild_pid_flips_the_ability_bit() {
+        // high half 0 after one step, leaving only the flipped bit
+        assert_eq!(wild_pid(0, 0), 0x00010000);
+        assert_eq!(wild_pid(0x0123456789ABCDEF, 0), 0x1DB80D64);
+        assert_eq!(wild_pid(0x0123456789ABCDEF, 5), 0xB6D30EA2);
+        assert_eq!(wild_pid(u64::MAX, 1000), 0x6A7A513D);
+    }
+
+    #[test]
+    fn search_finds_the_seed_and_ivs() {
+        let seed = initial_seed(&PROFILE, 0xC7A, &DATE_TIME, 0).unwrap();
+        let range = SearchRange {
+            years: 2011..=2011,
+            hours: 21..=21,
+            minutes: 30..=30,
+            seconds: 10..=20,
+            timer0: 0xC79..=0xC7B,
+            keys: vec![0, KEY_A],
+        };
+        let results = search(&PROFILE, &range, &Target::Seed(seed)).unwrap();
+        assert!(results.contains(&(DATE_TIME, 0xC7A, 0, seed)));
+
+        let ivs = ivs(seed, 1);
+        let target = Target::Ivs {
+            min: ivs,
+            max: ivs,
+            advance: 1,
+        };
+        let results = search(&PROFILE, &range, &target).unwrap();
+        assert!(results.contains(&(DATE_TIME, 0xC7A, 0, seed)));
+        assert!(results
+            .iter()
+            .all(|(_, _, _, seed)| self::ivs(*seed, 1) == ivs));
+
+        // the year is packed as year - 2000 into one bcd byte
+        let before_2000 = DateTime {
+            year: 1999,
+            ..DATE_TIME
+        };
+        assert_eq!(
+            initial_seed(&PROFILE, 0xC7A, &before_2000, 0),
+            Err(RngError::DateOutOfRange)
+        );
+        let range = SearchRange {
+            years: 1999..=2011,
+            ..range
+        };
+        assert_eq!(
+            search(&PROFILE, &range, &target),
+            Err(RngError::DateOutOfRange)
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use crate::gf2int::GF2Int;
+use ndarray::Array2;
+
+// affine form mask . x + constant of 64 unknown bits x
+pub type AffineBit = (u64, bool);
+
+// affine forms of the columns of ``matrix`` when the first 64 state bits are
+// the unknowns and the following 64 are fixed to ``fixed``
+pub fn affine_columns(matrix: &Array2<GF2Int>, fixed: u64) -> Vec<AffineBit> {
+    (0..matrix.shape()[1])
+        .map(|j| {
+            let mut mask = 0;
+            let mut constant = false;
+            for i in 0..64 {
+                mask |= (matrix[[i, j]].val as u64) << i;
+                constant ^= matrix[[i + 64, j]].val & ((fixed >> i) & 1) as u8 != 0;
+            }
+            (mask, constant)
+        })
+        .collect()
+}
+
+// incrementally eliminated system of equations over 64 unknown bits, pivot i
+// being the equation whose highest set bit is i
+#[derive(Clone, Debug)]
+pub struct AffineSystem {
+    pivots: [Option<AffineBit>; 64],
+}
+
+impl Default for AffineSystem {
+    fn default() -> Self {
+        AffineSystem { pivots: [None; 64] }
+    }
+}
+
+impl AffineSystem {
+    pub fn new() -> AffineSystem {
+        AffineSystem::default()
+    }
+
+    fn reduce(&self, (mut mask, mut rhs): AffineBit) -> AffineBit {
+        while mask != 0 {
+            let pivot = 63 - mask.leading_zeros() as usize;
+            match self.pivots[pivot] {
+                Some((pivot_mask, pivot_rhs)) => {
+                    mask ^= pivot_mask;
+                    rhs ^= pivot_rhs;
+                }
+                None => break,
+            }
+        }
+        (mask, rhs)
+    }
+
+    // adds mask . x = rhs, returning false if it contradicts the system
+    pub fn add_equation(&mut self, mask: u64, rhs: bool) -> bool {
+        self.insert(mask, rhs).is_ok()
+    }
+
+    // pivot the equation was stored at, if it was not redundant
+    fn insert(&mut self, mask: u64, rhs: bool) -> Result<Option<usize>, ()> {
+        let (mask, rhs) = self.reduce((mask, rhs));
+        if mask == 0 {
+            return if rhs { Err(()) } else { Ok(None) };
+        }
+        let pivot = 63 - mask.leading_zeros() as usize;
+        self.pivots[pivot] = Some((mask, rhs));
+        Ok(Some(pivot))
+    }
+
+    fn remove(&mut self, pivot: Option<usize>) {
+        if let Some(pivot) = pivot {
+            self.pivots[pivot] = None;
+        }
+    }
+
+    // value of an affine form if the system determines it
+    pub fn evaluate(&self, (mask, constant): AffineBit) -> Option<bool> {
+        let mut mask = mask;
+        let mut value = constant;
+        while mask != 0 {
+            let pivot = 63 - mask.leading_zeros() as usize;
+            let (pivot_mask, pivot_rhs) = self.pivots[pivot]?;
+            mask ^= pivot_mask;
+            value ^= pivot_rhs;
+        }
+        Some(value)
+    }
+
+    pub fn rank(&self) -> u32 {
+        self.pivots.iter().filter(|pivot| pivot.is_some()).count() as u32
+    }
+
+    // every x satisfying the system, 2 ** (64 - rank) of them
+    pub fn solutions(&self) -> impl Iterator<Item = u64> {
+        // back substitute into reduced row echelon form
+        let mut rows: Vec<(usize, u64, bool)> = vec![];
+        for pivot in 0..64 {
+            if let Some((mut mask, mut rhs)) = self.pivots[pivot] {
+                for (row_pivot, row_mask, row_rhs) in &rows {
+                    if (mask >> row_pivot) & 1 != 0 {
+                        mask ^= row_mask;
+                        rhs ^= row_rhs;
+                    }
+                }
+                for (_, row_mask, row_rhs) in &mut rows {
+                    if (*row_mask >> pivot) & 1 != 0 {
+                        *row_mask ^= mask;
+                        *row_rhs ^= rhs;
+                    }
+                }
+                rows.push((pivot, mask, rhs));
+            }
+        }
+        let free_bits: Vec<usize> = (0..64).filter(|&i| self.pivots[i].is_none()).collect();
+        let count = 1u128 << free_bits.len();
+        (0..count).map(move |n| {
+            let mut x = 0u64;
+            for (i, bit) in free_bits.iter().enumerate() {
+                x |= (((n >> i) & 1) as u64) << bit;
+            }
+            for (pivot, mask, rhs) in &rows {
+                let parity = (mask & !(1 << pivot) & x).count_ones() & 1 == 1;
+                x |= ((parity ^ rhs) as u64) << pivot;
+            }
+            x
+        })
+    }
+}
+
+// the low ``bits`` bits of lhs + rhs (mod 2 ** 64) match ``value`` at the
+// positions set in ``known``
+pub struct SumConstraint {
+    pub lhs: Vec<AffineBit>,
+    pub rhs: Vec<AffineBit>,
+    pub value: u64,
+    pub known: u64,
+    pub bits: usize,
+}
+
+// systems covering every x satisfying ``system`` and all ``constraints``,
+// found by walking the additions bit by bit and branching on the carries
+// whenever they are not determined
+pub fn solve_sum_constraints(
+    mut system: AffineSystem,
+    constraints: &[SumConstraint],
+) -> Vec<AffineSystem> {
+    let mut leaves = vec![];
+    solve_sum_bit(&mut system, constraints, 0, false, &mut leaves);
+    leaves
+}
+
+// equations are added in place and removed again when backtracking
+fn solve_sum_bit(
+    system: &mut AffineSystem,
+    constraints: &[SumConstraint],
+    bit: usize,
+    carry: bool,
+    leaves: &mut Vec<AffineSystem>,
+) {
+    let Some(constraint) = constraints.first() else {
+        leaves.push(system.clone());
+        return;
+    };
+    if bit == constraint.bits {
+        solve_sum_bit(system, &constraints[1..], 0, false, leaves);
+        return;
+    }
+    let (lhs_mask, lhs_constant) = constraint.lhs[bit];
+    let (rhs_mask, rhs_constant) = constraint.rhs[bit];
+    let xor_values: &[bool] = if (constraint.known >> bit) & 1 != 0 {
+        if (constraint.value >> bit) & 1 != carry as u64 {
+            &[true]
+        } else {
+            &[false]
+        }
+    } else {
+        &[false, true]
+    };
+    for &xor_value in xor_values {
+        // lhs ^ rhs = xor_value
+        let Ok(xor_pivot) =
+            system.insert(lhs_mask ^ rhs_mask, xor_value ^ lhs_constant ^ rhs_constant)
+        else {
+            continue;
+        };
+        if xor_value {
+            // exactly one of lhs, rhs is set so the carry passes through
+            solve_sum_bit(system, constraints, bit + 1, carry, leaves);
+        } else if let Some(lhs_value) = system.evaluate((lhs_mask, lhs_constant)) {
+            solve_sum_bit(system, constraints, bit + 1, lhs_value, leaves);
+        } else {
+            for lhs_value in [false, true] {
+                let lhs_pivot = system.insert(lhs_mask, lhs_value ^ lhs_constant);
+                solve_sum_bit(system, constraints, bit + 1, lhs_value, leaves);
+                system.remove(lhs_pivot.unwrap_or(None));
+            }
+        }
+        system.remove(xor_pivot);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::error::RngError;
 use num_traits::identities::{One, Zero};
 use std::fmt;
 use std::ops::{Add, Div, Mul, Sub};
@@ -11,6 +12,15 @@ impl GF2Int {
     pub fn new(val: u8) -> GF2Int {
         GF2Int { val: val & 1 }
     }
+
+    // division by the only nonzero element is the identity
+    pub fn checked_div(self, other: GF2Int) -> Result<GF2Int, RngError> {
+        if other.val == 0 {
+            Err(RngError::DivisionByZero)
+        } else {
+            Ok(self)
+        }
+    }
 }
 
 impl fmt::Display for GF2Int {
@@ -49,6 +59,7 @@ impl Add for GF2Int {
     type Output = GF2Int;
 
     #[inline(always)]
+    #[allow(clippy::suspicious_arithmetic_impl)]
     fn add(self, other: GF2Int) -> GF2Int {
         GF2Int {
             val: self.val ^ other.val,
@@ -59,6 +70,7 @@ impl Add for GF2Int {
 impl Sub for GF2Int {
     type Output = GF2Int;
 
+    #[allow(clippy::suspicious_arithmetic_impl)]
     fn sub(self, other: GF2Int) -> GF2Int {
         GF2Int {
             val: self.val ^ other.val,
@@ -70,6 +82,7 @@ impl Mul for GF2Int {
     type Output = GF2Int;
 
     #[inline(always)]
+    #[allow(clippy::suspicious_arithmetic_impl)]
     fn mul(self, other: GF2Int) -> GF2Int {
         GF2Int {
             val: self.val & other.val,
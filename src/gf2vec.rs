@@ -1,175 +1,634 @@
-use crate::gf2int::GF2Int;
-use std::cmp::min;
+use crate::{error::RngError, gf2int::GF2Int};
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::ToPrimitive;
+use std::cmp::Ordering;
+use std::ops::{Add, BitAnd, BitXor, BitXorAssign, Mul, Rem, Shl, Shr};
 
-#[derive(Debug, Clone, Copy)]
-pub struct GF2Vec128 {
-    pub state_low: u128,
-    pub state_high: u128,
+// polynomial over GF(2) stored as little-endian u64 limbs, bit i being the
+// coefficient of z ** i
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GF2Vec<const LIMBS: usize> {
+    pub limbs: [u64; LIMBS],
 }
 
-impl GF2Vec128 {
-    pub fn new(vec: Vec<GF2Int>) -> GF2Vec128 {
-        let mut state_low: u128 = 0;
-        let mut state_high: u128 = 0;
-        for i in 0..min(128, vec.len()) {
-            state_low |= (vec[i].val as u128) << i;
+// twice the state size so products of two reduced polynomials fit before
+// reduction
+pub type GF2Vec64 = GF2Vec<2>;
+pub type GF2Vec128 = GF2Vec<4>;
+pub type GF2Vec256 = GF2Vec<8>;
+pub type GF2Vec19937 = GF2Vec<624>;
+
+// jump counts, distances and group orders, u128 for states of up to 128 bits
+// and BigUint for wider ones
+pub trait Exponent: Integer + Clone + Send + Sync {
+    fn bits(&self) -> u64;
+    fn bit(&self, index: u64) -> bool;
+    fn to_biguint(&self) -> BigUint;
+    fn from_biguint(value: &BigUint) -> Option<Self>;
+}
+
+impl Exponent for u128 {
+    fn bits(&self) -> u64 {
+        (128 - self.leading_zeros()) as u64
+    }
+
+    fn bit(&self, index: u64) -> bool {
+        index < 128 && (self >> index) & 1 == 1
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        BigUint::from(*self)
+    }
+
+    fn from_biguint(value: &BigUint) -> Option<Self> {
+        value.to_u128()
+    }
+}
+
+impl Exponent for BigUint {
+    fn bits(&self) -> u64 {
+        BigUint::bits(self)
+    }
+
+    fn bit(&self, index: u64) -> bool {
+        BigUint::bit(self, index)
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        self.clone()
+    }
+
+    fn from_biguint(value: &BigUint) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+// the 32 bits of ``half`` moved to the even bit positions, i.e. squared as a
+// polynomial
+fn spread_bits(half: u32) -> u64 {
+    let mut x = half as u64;
+    x = (x | (x << 16)) & 0x0000_FFFF_0000_FFFF;
+    x = (x | (x << 8)) & 0x00FF_00FF_00FF_00FF;
+    x = (x | (x << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    x = (x | (x << 2)) & 0x3333_3333_3333_3333;
+    (x | (x << 1)) & 0x5555_5555_5555_5555
+}
+
+impl<const LIMBS: usize> GF2Vec<LIMBS> {
+    pub const BITS: u32 = 64 * LIMBS as u32;
+    pub const ZERO: Self = GF2Vec { limbs: [0; LIMBS] };
+    pub const ONE: Self = GF2Vec::from_u128(1);
+    pub const Z: Self = GF2Vec::from_u128(0b10);
+
+    pub fn new(vec: Vec<GF2Int>) -> Self {
+        let mut result = Self::ZERO;
+        for (i, bit) in vec.iter().enumerate().take(Self::BITS as usize) {
+            result.limbs[i / 64] |= (bit.val as u64) << (i % 64);
+        }
+        result
+    }
+
+    // bits beyond the capacity are dropped
+    pub const fn from_u128(value: u128) -> Self {
+        let mut limbs = [0; LIMBS];
+        if LIMBS > 0 {
+            limbs[0] = value as u64;
         }
-        for i in 128..min(256, vec.len()) {
-            state_high |= (vec[i].val as u128) << (i - 128);
+        if LIMBS > 1 {
+            limbs[1] = (value >> 64) as u64;
+        }
+        GF2Vec { limbs }
+    }
+
+    // little-endian, bytes beyond the capacity are dropped
+    pub fn from_le_bytes(bytes: &[u8]) -> Self {
+        let mut result = Self::ZERO;
+        for (i, byte) in bytes.iter().enumerate().take(8 * LIMBS) {
+            result.limbs[i / 8] |= (*byte as u64) << (8 * (i % 8));
         }
-        GF2Vec128 {
-            state_low,
-            state_high,
+        result
+    }
+
+    pub fn to_le_bytes(self) -> Vec<u8> {
+        self.limbs
+            .iter()
+            .flat_map(|limb| limb.to_le_bytes())
+            .collect()
+    }
+
+    // low 128 coefficients, the whole polynomial once reduced modulo a
+    // polynomial of degree at most 128
+    pub fn low_u128(self) -> u128 {
+        let mut result = 0;
+        for (i, limb) in self.limbs.iter().enumerate().take(2) {
+            result |= (*limb as u128) << (64 * i);
         }
+        result
+    }
+
+    pub fn bit(self, index: u32) -> bool {
+        index < Self::BITS && (self.limbs[index as usize / 64] >> (index % 64)) & 1 != 0
     }
 
     pub fn last_bit_pos(self) -> u32 {
-        // common enough case to account for
-        if self.state_high == 1 {
-            return 128;
-        } else if self.state_high != 0 {
-            let mut state = self.state_high;
-            let mut result = 128;
-            while state != 0 {
-                state >>= 1;
-                result += 1;
-            }
-            return result;
-        } else if self.state_low != 0 {
-            let mut state = self.state_low;
-            let mut result = 0;
-            while state != 0 {
-                state >>= 1;
-                result += 1;
-            }
-            return result;
-        } else {
+        if self.is_zero() {
             // should never happen
             panic!("Getting MSSB of 0 vector");
         }
+        self.degree() + 1
     }
 
     pub fn is_zero(self) -> bool {
-        return (self.state_high == 0) && (self.state_low == 0);
+        self.limbs.iter().all(|limb| *limb == 0)
     }
 
     pub fn is_one(self) -> bool {
-        return (self.state_high == 0) && (self.state_low == 1);
+        self == Self::ONE
+    }
+
+    pub fn checked_last_bit_pos(self) -> Result<u32, RngError> {
+        if self.is_zero() {
+            Err(RngError::ZeroPolynomial)
+        } else {
+            Ok(self.last_bit_pos())
+        }
+    }
+
+    pub fn checked_degree(self) -> Result<u32, RngError> {
+        if self.is_zero() {
+            Err(RngError::ZeroPolynomial)
+        } else {
+            Ok(self.degree())
+        }
+    }
+
+    pub fn degree(self) -> u32 {
+        match self.limbs.iter().rposition(|limb| *limb != 0) {
+            Some(i) => 64 * i as u32 + 63 - self.limbs[i].leading_zeros(),
+            None => panic!("Getting degree of 0 vector"),
+        }
+    }
+
+    // index of the highest nonzero limb
+    fn top_limb(self) -> Option<usize> {
+        self.limbs.iter().rposition(|limb| *limb != 0)
+    }
+
+    // self ^= rhs << shift for rhs nonzero only up to limb ``rhs_top``, bits
+    // beyond the capacity being dropped
+    fn xor_shifted(&mut self, rhs: &Self, rhs_top: usize, shift: u32) {
+        let (words, bits) = ((shift / 64) as usize, shift % 64);
+        for i in 0..=rhs_top.min(LIMBS.saturating_sub(words + 1)) {
+            self.limbs[i + words] ^= rhs.limbs[i] << bits;
+            if bits != 0 && i + words + 1 < LIMBS {
+                self.limbs[i + words + 1] ^= rhs.limbs[i] >> (64 - bits);
+            }
+        }
     }
 
-    pub fn modulo(self, rhs: GF2Vec128) -> GF2Vec128 {
+    fn xor_limbs(mut self, rhs: Self) -> Self {
+        for (limb, rhs_limb) in self.limbs.iter_mut().zip(rhs.limbs) {
+            *limb ^= rhs_limb;
+        }
+        self
+    }
+
+    // reduces from the top limb down, so the degree is never rescanned from
+    // the top of the array
+    pub fn modulo(self, rhs: Self) -> Self {
+        let rhs_degree = rhs.degree();
+        let rhs_top = rhs_degree as usize / 64;
         let mut polynomial = self;
-        let last_bit_pos = rhs.last_bit_pos();
-        if polynomial.shr(last_bit_pos).is_zero() {
-            return polynomial;
-        }
-        let poly_mssb = polynomial.last_bit_pos();
-        let shift_num = poly_mssb - last_bit_pos;
-        let mut modulus = rhs.shl(shift_num);
-        for shift_pos in 0..shift_num + 1 {
-            if polynomial.is_zero() {
-                return GF2Vec128 {
-                    state_low: 0,
-                    state_high: 0,
-                };
+        let mut top = LIMBS;
+        while top > 0 {
+            let limb = polynomial.limbs[top - 1];
+            if limb == 0 {
+                top -= 1;
+                continue;
             }
-            if polynomial.shr(poly_mssb - shift_pos).is_one() {
-                polynomial = polynomial.bitxor(modulus);
+            let degree = 64 * (top as u32 - 1) + 63 - limb.leading_zeros();
+            if degree < rhs_degree {
+                break;
             }
-            modulus = modulus.shr(1);
+            polynomial.xor_shifted(&rhs, rhs_top, degree - rhs_degree);
         }
         polynomial
     }
 
-    pub fn mul(self, rhs: GF2Vec128) -> GF2Vec128 {
-        let mut multiplicand = self;
-        let mut multiplier = rhs;
-        let mut result = GF2Vec128 {
-            state_low: 0,
-            state_high: 0,
-        };
+    pub fn try_modulo(self, rhs: Self) -> Result<Self, RngError> {
+        rhs.checked_degree()?;
+        Ok(self.modulo(rhs))
+    }
 
-        while !multiplier.is_zero() {
-            if (multiplier.state_low & 1) != 0 {
-                result = result.bitxor(multiplicand);
+    pub fn div_rem(self, rhs: Self) -> (Self, Self) {
+        let mut remainder = self;
+        let mut quotient = Self::ZERO;
+        let rhs_degree = rhs.degree();
+        let rhs_top = rhs_degree as usize / 64;
+        while !remainder.is_zero() && remainder.degree() >= rhs_degree {
+            let shift = remainder.degree() - rhs_degree;
+            quotient.limbs[shift as usize / 64] ^= 1 << (shift % 64);
+            remainder.xor_shifted(&rhs, rhs_top, shift);
+        }
+        (quotient, remainder)
+    }
+
+    // squaring is linear over GF(2), only spreading the bits apart
+    pub fn square(self) -> Self {
+        let mut result = Self::ZERO;
+        for (i, limb) in self.limbs.iter().enumerate().take(LIMBS.div_ceil(2)) {
+            result.limbs[2 * i] = spread_bits(*limb as u32);
+            if 2 * i + 1 < LIMBS {
+                result.limbs[2 * i + 1] = spread_bits((*limb >> 32) as u32);
             }
-            multiplicand = multiplicand.shl(1);
-            multiplier = multiplier.shr(1);
         }
         result
     }
 
-    pub fn modpow(self, rhs: u128, modulus: GF2Vec128) -> GF2Vec128 {
-        let mut power = rhs;
-        let mut base = self;
-        let mut result = GF2Vec128 {
-            state_low: 1,
-            state_high: 0,
-        };
-        // exponentiation by squares
-        while power > 0 {
-            if (power & 1) == 1 {
-                result = result.mul(base).modulo(modulus);
+    pub fn modpow<E: Exponent>(self, rhs: E, modulus: Self) -> Self {
+        let mut result = Self::ONE;
+        // left to right square and multiply
+        for i in (0..rhs.bits()).rev() {
+            result = result.square().modulo(modulus);
+            if rhs.bit(i) {
+                result = (result * self).modulo(modulus);
             }
-            power >>= 1;
-            base = base.mul(base).modulo(modulus);
         }
-
         result
     }
 
-    pub fn bitxor(self, rhs: GF2Vec128) -> GF2Vec128 {
-        return GF2Vec128 {
-            state_low: self.state_low ^ rhs.state_low,
-            state_high: self.state_high ^ rhs.state_high,
-        };
+    pub fn gcd(self, rhs: Self) -> Self {
+        let mut a = self;
+        let mut b = rhs;
+        while !b.is_zero() {
+            (a, b) = (b, a.modulo(b));
+        }
+        a
     }
 
-    pub fn shr(self, rhs: u32) -> GF2Vec128 {
-        if rhs == 0 {
-            return self;
+    // (gcd, s, t) with s * self + t * rhs = gcd
+    pub fn extended_gcd(self, rhs: Self) -> (Self, Self, Self) {
+        let (mut old_r, mut r) = (self, rhs);
+        let (mut old_s, mut s) = (Self::ONE, Self::ZERO);
+        let (mut old_t, mut t) = (Self::ZERO, Self::ONE);
+        while !r.is_zero() {
+            let (quotient, remainder) = old_r.div_rem(r);
+            (old_r, r) = (r, remainder);
+            (old_s, s) = (s, old_s + quotient * s);
+            (old_t, t) = (t, old_t + quotient * t);
         }
-        if rhs >= 128 {
-            return GF2Vec128 {
-                state_low: self.state_high >> (rhs - 128),
-                state_high: 0,
-            };
+        (old_r, old_s, old_t)
+    }
+
+    pub fn inverse_mod(self, modulus: Self) -> Option<Self> {
+        let (gcd, inverse, _) = self.modulo(modulus).extended_gcd(modulus);
+        if gcd.is_one() {
+            Some(inverse.modulo(modulus))
+        } else {
+            None
         }
-        let state_low = (self.state_low >> rhs) | (self.state_high << (128 - rhs));
-        let state_high = self.state_high >> rhs;
-        GF2Vec128 {
-            state_low,
-            state_high,
+    }
+
+    pub fn try_inverse_mod(self, modulus: Self) -> Result<Self, RngError> {
+        modulus.checked_degree()?;
+        self.inverse_mod(modulus).ok_or(RngError::NotInvertible)
+    }
+}
+
+impl<const LIMBS: usize> From<u64> for GF2Vec<LIMBS> {
+    fn from(value: u64) -> Self {
+        GF2Vec::from_u128(value as u128)
+    }
+}
+
+impl<const LIMBS: usize> From<u128> for GF2Vec<LIMBS> {
+    fn from(value: u128) -> Self {
+        GF2Vec::from_u128(value)
+    }
+}
+
+impl<const LIMBS: usize, const N: usize> From<[u8; N]> for GF2Vec<LIMBS> {
+    fn from(bytes: [u8; N]) -> Self {
+        GF2Vec::from_le_bytes(&bytes)
+    }
+}
+
+// ordered by value, i.e. by degree first
+impl<const LIMBS: usize> Ord for GF2Vec<LIMBS> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs.iter().rev().cmp(other.limbs.iter().rev())
+    }
+}
+
+impl<const LIMBS: usize> PartialOrd for GF2Vec<LIMBS> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// addition over GF(2) is xor
+impl<const LIMBS: usize> Add for GF2Vec<LIMBS> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        self.xor_limbs(rhs)
+    }
+}
+
+impl<const LIMBS: usize> BitXor for GF2Vec<LIMBS> {
+    type Output = Self;
+    fn bitxor(self, rhs: Self) -> Self {
+        self.xor_limbs(rhs)
+    }
+}
+
+impl<const LIMBS: usize> BitXorAssign for GF2Vec<LIMBS> {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        *self = *self ^ rhs;
+    }
+}
+
+impl<const LIMBS: usize> BitAnd for GF2Vec<LIMBS> {
+    type Output = Self;
+    fn bitand(self, rhs: Self) -> Self {
+        let mut result = self;
+        for (limb, rhs_limb) in result.limbs.iter_mut().zip(rhs.limbs) {
+            *limb &= rhs_limb;
         }
+        result
     }
+}
 
-    pub fn shl(self, rhs: u32) -> GF2Vec128 {
-        if rhs == 0 {
-            return self;
+impl<const LIMBS: usize> Mul for GF2Vec<LIMBS> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        let mut result = Self::ZERO;
+        let Some(top) = self.top_limb() else {
+            return result;
+        };
+        for (i, limb) in rhs.limbs.iter().enumerate() {
+            let mut limb = *limb;
+            while limb != 0 {
+                let bit = limb.trailing_zeros();
+                result.xor_shifted(&self, top, 64 * i as u32 + bit);
+                limb &= limb - 1;
+            }
         }
-        if rhs >= 128 {
-            return GF2Vec128 {
-                state_low: 0,
-                state_high: self.state_low << (rhs - 128),
-            };
+        result
+    }
+}
+
+impl<const LIMBS: usize> Rem for GF2Vec<LIMBS> {
+    type Output = Self;
+    fn rem(self, rhs: Self) -> Self {
+        self.modulo(rhs)
+    }
+}
+
+// whole limbs move first, then the remaining bits carry between limbs
+impl<const LIMBS: usize> Shr<u32> for GF2Vec<LIMBS> {
+    type Output = Self;
+    fn shr(self, rhs: u32) -> Self {
+        let (words, bits) = ((rhs / 64) as usize, rhs % 64);
+        let mut result = Self::ZERO;
+        for i in 0..LIMBS.saturating_sub(words) {
+            result.limbs[i] = self.limbs[i + words] >> bits;
+            if bits != 0 && i + words + 1 < LIMBS {
+                result.limbs[i] |= self.limbs[i + words + 1] << (64 - bits);
+            }
         }
-        let state_high = (self.state_high << rhs) | (self.state_low >> (128 - rhs));
-        let state_low = self.state_low << rhs;
-        GF2Vec128 {
-            state_low,
-            state_high,
+        result
+    }
+}
+
+impl<const LIMBS: usize> Shl<u32> for GF2Vec<LIMBS> {
+    type Output = Self;
+    fn shl(self, rhs: u32) -> Self {
+        let (words, bits) = ((rhs / 64) as usize, rhs % 64);
+        let mut result = Self::ZERO;
+        for i in words..LIMBS {
+            result.limbs[i] = self.limbs[i - words] << bits;
+            if bits != 0 && i > words {
+                result.limbs[i] |= self.limbs[i - words - 1] >> (64 - bits);
+            }
         }
+        result
     }
 }
 
-pub fn base_z_modpow(power: u128, modulus: GF2Vec128) -> GF2Vec128 {
-    let base = GF2Vec128 {
-        state_low: 0b10, // z ** 1
-        state_high: 0,
-    };
-    base.modpow(power, modulus)
+// ``modpow`` of z, multiplying by z being a shift
+pub fn base_z_modpow<E: Exponent, const LIMBS: usize>(
+    power: E,
+    modulus: GF2Vec<LIMBS>,
+) -> GF2Vec<LIMBS> {
+    let mut result = GF2Vec::ONE;
+    for i in (0..power.bits()).rev() {
+        result = result.square().modulo(modulus);
+        if power.bit(i) {
+            result = (result << 1).modulo(modulus);
+        }
+    }
+    result
 }
 
-pub fn compute_jump_poly(jmp: u128, char_poly: GF2Vec128) -> GF2Vec128 {
+pub fn compute_jump_poly<E: Exponent, const LIMBS: usize>(
+    jmp: E,
+    char_poly: GF2Vec<LIMBS>,
+) -> GF2Vec<LIMBS> {
     base_z_modpow(jmp, char_poly)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    // the former two-u128 representation, (low, high) halves of 256 bits
+    fn reference_mul(a: u128, b: u128) -> (u128, u128) {
+        let (mut low, mut high) = (0u128, 0u128);
+        for i in 0..128 {
+            if (b >> i) & 1 == 1 {
+                low ^= a << i;
+                if i != 0 {
+                    high ^= a >> (128 - i);
+                }
+            }
+        }
+        (low, high)
+    }
+
+    fn reference_modulo((mut low, mut high): (u128, u128), modulus: u128) -> u128 {
+        let degree = 127 - modulus.leading_zeros();
+        for bit in (degree..256).rev() {
+            let set = if bit >= 128 {
+                (high >> (bit - 128)) & 1 == 1
+            } else {
+                (low >> bit) & 1 == 1
+            };
+            if set {
+                let shift = bit - degree;
+                low ^= modulus << shift;
+                if shift >= 128 {
+                    high ^= modulus << (shift - 128);
+                } else if shift != 0 {
+                    high ^= modulus >> (128 - shift);
+                }
+            }
+        }
+        low
+    }
+
+    fn halves(value: GF2Vec128) -> (u128, u128) {
+        (value.low_u128(), (value >> 128).low_u128())
+    }
+
+    #[test]
+    fn shifts_cross_limb_boundaries() {
+        let value = GF2Vec128::from(0x8000_0000_0000_0001u64);
+        assert_eq!((value << 1).limbs, [2, 1, 0, 0]);
+        assert_eq!((value << 64).limbs, [0, 0x8000_0000_0000_0001, 0, 0]);
+        assert_eq!(
+            (value << 127).limbs,
+            [0, 0x8000_0000_0000_0000, 0x4000_0000_0000_0000, 0]
+        );
+        assert_eq!((value << 255).limbs, [0, 0, 0, 0x8000_0000_0000_0000]);
+        assert_eq!(value << 256, GF2Vec128::ZERO);
+        assert_eq!(value << 130 >> 130, value);
+        assert_eq!(GF2Vec128::ONE << 200 >> 137, GF2Vec128::ONE << 63);
+        assert_eq!((value >> 1).limbs, [0x4000_0000_0000_0000, 0, 0, 0]);
+    }
+
+    #[test]
+    fn degree_uses_the_highest_limb() {
+        assert_eq!(GF2Vec128::ONE.degree(), 0);
+        assert_eq!(GF2Vec128::from(u64::MAX).degree(), 63);
+        assert_eq!((GF2Vec128::ONE << 64).degree(), 64);
+        assert_eq!((GF2Vec128::ONE << 255).degree(), 255);
+        assert_eq!((GF2Vec128::ONE << 200).last_bit_pos(), 201);
+        assert_eq!(
+            GF2Vec128::ZERO.checked_degree(),
+            Err(RngError::ZeroPolynomial)
+        );
+    }
+
+    #[test]
+    fn ordering_is_by_value() {
+        let small = GF2Vec128::from(u64::MAX);
+        let large = GF2Vec128::ONE << 64;
+        assert!(small < large);
+        assert!(GF2Vec128::ONE << 192 > GF2Vec128::from(u128::MAX));
+        let mut values = vec![large, GF2Vec128::ZERO, small, GF2Vec128::ONE];
+        values.sort();
+        assert_eq!(values, [GF2Vec128::ZERO, GF2Vec128::ONE, small, large]);
+    }
+
+    #[test]
+    fn from_le_bytes_fills_limbs_in_order() {
+        let mut bytes = [0u8; 16];
+        bytes[0] = 0x01;
+        bytes[8] = 0x02;
+        bytes[15] = 0x80;
+        let value = GF2Vec128::from(bytes);
+        assert_eq!(value.limbs, [1, 0x8000_0000_0000_0002, 0, 0]);
+        assert_eq!(GF2Vec128::from_le_bytes(&value.to_le_bytes()), value);
+        // bytes beyond the capacity are dropped
+        assert_eq!(
+            GF2Vec64::from_le_bytes(&[0xff; 20]),
+            GF2Vec64::from(u128::MAX)
+        );
+    }
+
+    #[test]
+    fn mul_and_modulo_match_the_two_u128_results() {
+        // x^64 + 1 squared
+        let value = (GF2Vec128::ONE << 64) + GF2Vec128::ONE;
+        assert_eq!(value * value, (GF2Vec128::ONE << 128) + GF2Vec128::ONE);
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let (a, b): (u128, u128) = (rng.gen(), rng.gen());
+            let modulus = rng.gen::<u128>() | 1 << 127;
+            let product = GF2Vec128::from(a) * GF2Vec128::from(b);
+            assert_eq!(halves(product), reference_mul(a, b));
+            assert_eq!(
+                (product % GF2Vec128::from(modulus)).low_u128(),
+                reference_modulo(reference_mul(a, b), modulus)
+            );
+            assert_eq!(
+                GF2Vec128::from(a).square(),
+                GF2Vec128::from(a) * GF2Vec128::from(a)
+            );
+        }
+    }
+
+    #[test]
+    fn fallible_operations_reject_a_zero_modulus() {
+        let value = GF2Vec128::from(0b1011u64);
+        assert_eq!(
+            value.try_modulo(GF2Vec128::ZERO),
+            Err(RngError::ZeroPolynomial)
+        );
+        assert_eq!(
+            value.try_inverse_mod(GF2Vec128::ZERO),
+            Err(RngError::ZeroPolynomial)
+        );
+        // z shares the factor z with z^2 + z
+        assert_eq!(
+            GF2Vec128::Z.try_inverse_mod(GF2Vec128::from(0b110u64)),
+            Err(RngError::NotInvertible)
+        );
+        let modulus = GF2Vec128::from(0b10011u64);
+        let inverse = value.try_inverse_mod(modulus).unwrap();
+        assert!((value * inverse % modulus).is_one());
+    }
+
+    #[test]
+    fn modpow_agrees_across_exponent_types() {
+        let modulus = GF2Vec128::from(0x1_0000_0000_0000_001Bu128);
+        let base = GF2Vec128::from(0x1234_5678u64);
+        let power = 0xdead_beef_1234_5678_9abc_u128;
+        assert_eq!(
+            base.modpow(power, modulus),
+            base.modpow(BigUint::from(power), modulus)
+        );
+        assert_eq!(
+            base_z_modpow(power, modulus),
+            GF2Vec128::Z.modpow(power, modulus)
+        );
+        assert_eq!(base.modpow(0u128, modulus), GF2Vec128::ONE);
+    }
+
+    #[test]
+    fn division_gcd_and_inverse_known_answers() {
+        // x^5 + x + 1 = (x^2 + x + 1)(x^3 + x^2 + 1)
+        let product = GF2Vec128::from(0b100011u64);
+        let (quotient, remainder) = product.div_rem(GF2Vec128::from(0b111u64));
+        assert_eq!(quotient, GF2Vec128::from(0b1101u64));
+        assert!(remainder.is_zero());
+        // x^5 = (x^2 + 1)(x^3 + x + 1) + x^2 + x + 1
+        assert_eq!(
+            (GF2Vec128::ONE << 5).div_rem(GF2Vec128::from(0b1011u64)),
+            (GF2Vec128::from(0b101u64), GF2Vec128::from(0b111u64))
+        );
+
+        // gcd((x + 1)^2 (x^2 + x + 1), (x + 1)(x^3 + x + 1)) = x + 1
+        let a = GF2Vec128::from(0b101u64) * GF2Vec128::from(0b111u64);
+        let b = GF2Vec128::from(0b11u64) * GF2Vec128::from(0b1011u64);
+        let (gcd, s, t) = a.extended_gcd(b);
+        assert_eq!(gcd, GF2Vec128::from(0b11u64));
+        assert_eq!(s * a + t * b, gcd);
+
+        // x (x^3 + 1) = x^4 + x = 1 mod x^4 + x + 1
+        let modulus = GF2Vec128::from(0b10011u64);
+        assert_eq!(
+            GF2Vec128::Z.inverse_mod(modulus),
+            Some(GF2Vec128::from(0b1001u64))
+        );
+        // and across limbs, z ** -1 = z ** (2 ** 127 - 2) mod x^127 + x + 1
+        let modulus = (GF2Vec128::ONE << 127) + GF2Vec128::from(0b11u64);
+        assert_eq!(
+            GF2Vec128::Z.inverse_mod(modulus),
+            Some(base_z_modpow((u128::MAX >> 1) - 1, modulus))
+        );
+        assert_eq!(GF2Vec128::ZERO.inverse_mod(modulus), None);
+    }
+}
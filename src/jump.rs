@@ -0,0 +1,251 @@
+use crate::{
+    error::RngError,
+    gf2int::GF2Int,
+    gf2vec::{compute_jump_poly, Exponent, GF2Vec},
+    mat_builder::MatInverse,
+    pohlig_hellman::try_pohlig_hellman,
+    rng::RNG,
+};
+use ndarray::Array2;
+use num_traits::Zero;
+
+// shared jump and distance logic for linear generators of any state size,
+// ``Default`` being the all-zero state
+
+// window width balancing the 2 ** width table additions against the
+// degree / width additions of the horner steps
+fn window_width(degree: u32) -> u32 {
+    (degree.max(2).ilog2() / 2).max(1)
+}
+
+// jump_poly(F) applied to the state by horner's method over sliding windows
+// (haramoto et al.), needing about degree steps but only degree / width state
+// additions, which dominate for large states
+pub fn apply_jump_poly<R: RNG + Clone + Default, const LIMBS: usize>(
+    rng: &mut R,
+    jump_poly: GF2Vec<LIMBS>,
+    degree: u32,
+) {
+    let width = window_width(degree);
+    // table[v] = sum of F ** k (state) over the set bits k of v
+    let mut table = vec![R::default(); 1 << width];
+    let mut power = rng.clone();
+    for k in 0..width {
+        for v in 0..(1 << k) {
+            let mut sum = table[v].clone();
+            sum.add_state(&power);
+            table[v | (1 << k)] = sum;
+        }
+        power.next_state();
+    }
+
+    let mut result = R::default();
+    let mut bit = degree.min(GF2Vec::<LIMBS>::BITS);
+    let mut is_zero = true;
+    while bit > 0 {
+        if !jump_poly.bit(bit - 1) {
+            if !is_zero {
+                result.next_state();
+            }
+            bit -= 1;
+            continue;
+        }
+        // window from the set bit down to the lowest set bit within reach
+        let mut low = bit.saturating_sub(width);
+        while !jump_poly.bit(low) {
+            low += 1;
+        }
+        let mut window = 0;
+        for k in low..bit {
+            window |= (jump_poly.bit(k) as usize) << (k - low);
+        }
+        if !is_zero {
+            for _ in low..bit {
+                result.next_state();
+            }
+        }
+        result.add_state(&table[window]);
+        is_zero = false;
+        bit = low;
+    }
+    *rng = result;
+}
+
+// the reference jump() and long_jump() xor the states at the set bits of
+// their constants, which are jump polynomials given as u64 words
+pub fn jump_with_constants<R: RNG + Clone + Default, const LIMBS: usize>(
+    rng: &mut R,
+    words: &[u64],
+) {
+    let mut jump_poly = GF2Vec::<LIMBS>::ZERO;
+    jump_poly.limbs[..words.len()].copy_from_slice(words);
+    apply_jump_poly(rng, jump_poly, 64 * words.len() as u32);
+}
+
+// ``char_poly`` of the generator with ``LIMBS`` twice its state size, to be
+// computed once per generator
+pub fn jump<R, const LIMBS: usize>(rng: &mut R, jmp: R::AdvanceInt, char_poly: GF2Vec<LIMBS>)
+where
+    R: RNG + Clone + Default,
+    R::AdvanceInt: Exponent,
+{
+    // can be precomputed via jump table
+    let jump_poly = compute_jump_poly(jmp, char_poly);
+    apply_jump_poly(rng, jump_poly, char_poly.degree());
+}
+
+// ``jump`` refusing the all-zero state, which every jump maps to itself
+pub fn try_jump<R, const LIMBS: usize>(
+    rng: &mut R,
+    jmp: R::AdvanceInt,
+    char_poly: GF2Vec<LIMBS>,
+) -> Result<(), RngError>
+where
+    R: RNG + Clone + Default,
+    R::AdvanceInt: Exponent,
+{
+    if rng.state().is_zero() {
+        return Err(RngError::ZeroState);
+    }
+    let jump_poly = compute_jump_poly(jmp, char_poly);
+    apply_jump_poly(rng, jump_poly, char_poly.checked_degree()?);
+    Ok(())
+}
+
+// rows are the states at advances 0..size from ``origin``
+pub fn jump_application_mat<R>(origin: R, size: usize) -> Array2<GF2Int>
+where
+    R: RNG,
+    R::AdvanceInt: Exponent,
+{
+    let mut start = origin;
+    let mut jump_application_mat = Array2::<GF2Int>::zeros((size, size));
+    for i in 0..size {
+        let state = start.state();
+        for j in 0..size {
+            jump_application_mat[[i, j]] = GF2Int::new(Exponent::bit(&state, j as u64) as u8);
+        }
+        start.next_state();
+    }
+    jump_application_mat
+}
+
+pub fn state_mat<R>(mut state: R, size: usize) -> Array2<GF2Int>
+where
+    R: RNG,
+    R::AdvanceInt: Exponent,
+{
+    let mut state_mat = Array2::<GF2Int>::zeros((1, size));
+    let bits = state.state();
+    for i in 0..size {
+        state_mat[[0, i]] = GF2Int::new(Exponent::bit(&bits, i as u64) as u8);
+    }
+    state_mat
+}
+
+pub fn jump_poly_from_state<R, const LIMBS: usize>(
+    jump_application_mat_inv: &Array2<GF2Int>,
+    end: R,
+) -> GF2Vec<LIMBS>
+where
+    R: RNG,
+    R::AdvanceInt: Exponent,
+{
+    let size = jump_application_mat_inv.shape()[0];
+    let jump_poly_mat = state_mat(end, size).dot(jump_application_mat_inv);
+    GF2Vec::new(jump_poly_mat.iter().copied().collect())
+}
+
+// distance for generators whose characteristic polynomial is primitive,
+// ``primes`` being the prime factors of ``order`` = 2 ** n - 1, failing for
+// all-zero states, which have no distance to anything else, and for states on
+// different orbits
+pub fn try_distance<R, const LIMBS: usize>(
+    mut origin: R,
+    mut other: R,
+    char_poly: GF2Vec<LIMBS>,
+    order: R::AdvanceInt,
+    primes: Vec<R::AdvanceInt>,
+) -> Result<R::AdvanceInt, RngError>
+where
+    R: RNG,
+    R::AdvanceInt: Exponent,
+{
+    if origin.state().is_zero() || other.state().is_zero() {
+        return Err(RngError::ZeroState);
+    }
+    let size = char_poly.checked_degree()? as usize;
+
+    let jump_application_mat_inv = jump_application_mat(origin, size).try_inverse()?;
+    let jump_poly = jump_poly_from_state(&jump_application_mat_inv, other);
+    let z = GF2Vec::Z;
+    try_pohlig_hellman(
+        z,
+        // z ** -1
+        z.try_inverse_mod(char_poly)?,
+        jump_poly,
+        char_poly,
+        order,
+        primes,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mt::Mt19937, poly_analysis::charpoly, xoroshiro::Xoroshiro128Plus,
+        xoroshiro::Xoroshiro64Star,
+    };
+    use num_bigint::BigUint;
+
+    // outputs compared after a jump, more than a full ring buffer
+    const N_CHECKED: usize = 700;
+
+    #[test]
+    fn mt_jump_matches_advance() {
+        let origin = Mt19937::new(5489);
+        for n in [0usize, 1, 623, 624, 625, 9999, 123456] {
+            let mut jumped = origin.clone();
+            let mut advanced = origin.clone();
+            RNG::jump(&mut jumped, BigUint::from(n));
+            advanced.advance(n);
+            for _ in 0..N_CHECKED {
+                assert_eq!(jumped.next_u32(), advanced.next_u32(), "jump {n}");
+            }
+        }
+    }
+
+    #[test]
+    fn jump_by_2_64_matches_reference_jump() {
+        let mut jumped = Xoroshiro128Plus::new(0x1234_5678);
+        let mut reference = jumped;
+        let char_poly = charpoly::<Xoroshiro128Plus, 4>();
+        jump(&mut jumped, 1 << 64, char_poly);
+        reference.jump_2_64();
+        assert_eq!(
+            [jumped.seed_0, jumped.seed_1],
+            [reference.seed_0, reference.seed_1]
+        );
+    }
+
+    #[test]
+    fn distance_round_trips_jump() {
+        let char_poly = charpoly::<Xoroshiro64Star, 2>();
+        let order = u64::MAX as u128;
+        let primes = vec![3, 5, 17, 257, 641, 65537, 6700417];
+        let origin = Xoroshiro64Star::new(0xdead, 0xbeef);
+        for n in [0, 1, 0x1234_5678_9abc, order - 1] {
+            let mut target = origin;
+            jump(&mut target, n, char_poly);
+            assert_eq!(
+                try_distance(origin, target, char_poly, order, primes.clone()),
+                Ok(n)
+            );
+        }
+        assert_eq!(
+            try_distance(origin, Xoroshiro64Star::default(), char_poly, order, primes),
+            Err(RngError::ZeroState)
+        );
+    }
+}
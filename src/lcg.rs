@@ -0,0 +1,256 @@
+use crate::{
+    error::RngError,
+    export::serialize_hex,
+    notation::{format_words, parse_words},
+};
+use serde::Serialize;
+use std::{fmt, str::FromStr};
+
+// seed = seed * MULT + ADD (mod 2 ** 32), returning the new seed
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Lcg32<const MULT: u32, const ADD: u32> {
+    #[serde(serialize_with = "serialize_hex")]
+    pub seed: u32,
+}
+
+// gen 3 and 4
+pub type PokeRng = Lcg32<0x41C64E6D, 0x6073>;
+// colosseum/xd
+pub type XdRng = Lcg32<0x343FD, 0x269EC3>;
+
+// inverse of an odd multiplier by newton's iteration, each doubling the
+// number of correct low bits
+const fn inverse_u32(mult: u32) -> u32 {
+    let mut inverse = mult;
+    let mut i = 0;
+    while i < 5 {
+        inverse = inverse.wrapping_mul(2u32.wrapping_sub(mult.wrapping_mul(inverse)));
+        i += 1;
+    }
+    inverse
+}
+
+impl<const MULT: u32, const ADD: u32> fmt::Display for Lcg32<MULT, ADD> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format_words(f, &[self.seed])
+    }
+}
+
+impl<const MULT: u32, const ADD: u32> FromStr for Lcg32<MULT, ADD> {
+    type Err = RngError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: [u32; 1] = parse_words(s)?;
+        Ok(Lcg32::new(words[0]))
+    }
+}
+
+impl<const MULT: u32, const ADD: u32> Lcg32<MULT, ADD> {
+    pub const REVERSE_MULT: u32 = inverse_u32(MULT);
+    pub const REVERSE_ADD: u32 = Self::REVERSE_MULT.wrapping_mul(ADD).wrapping_neg();
+
+    pub fn new(seed: u32) -> Self {
+        Lcg32 { seed }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.seed = self.seed.wrapping_mul(MULT).wrapping_add(ADD);
+        self.seed
+    }
+
+    pub fn next_u16(&mut self) -> u16 {
+        (self.next_u32() >> 16) as u16
+    }
+
+    // steps back, returning the seed before the step
+    pub fn previous_u32(&mut self) -> u32 {
+        let seed = self.seed;
+        self.seed = self
+            .seed
+            .wrapping_mul(Self::REVERSE_MULT)
+            .wrapping_add(Self::REVERSE_ADD);
+        seed
+    }
+
+    pub fn previous_u16(&mut self) -> u16 {
+        (self.previous_u32() >> 16) as u16
+    }
+
+    // composes the affine step by squaring, O(log advance)
+    pub fn advance(&mut self, advance: u32) {
+        self.seed = jump_affine(self.seed, MULT, ADD, advance);
+    }
+
+    pub fn reverse(&mut self, advance: u32) {
+        self.seed = jump_affine(self.seed, Self::REVERSE_MULT, Self::REVERSE_ADD, advance);
+    }
+
+    // number of steps from ``self`` to ``other``, bit by bit from the low end
+    // as the low k bits of the seed have period 2 ** k
+    pub fn distance(&self, other: Self) -> u32 {
+        let mut distance = 0;
+        let mut seed = self.seed;
+        let (mut mult, mut add) = (MULT, ADD);
+        for bit in 0..32 {
+            let mask = 1 << bit;
+            if (seed ^ other.seed) & mask != 0 {
+                seed = seed.wrapping_mul(mult).wrapping_add(add);
+                distance |= mask;
+            }
+            add = add.wrapping_mul(mult.wrapping_add(1));
+            mult = mult.wrapping_mul(mult);
+        }
+        distance
+    }
+}
+
+fn jump_affine(seed: u32, mult: u32, add: u32, advance: u32) -> u32 {
+    let (mut mult, mut add) = (mult, add);
+    let mut seed = seed;
+    let mut advance = advance;
+    while advance != 0 {
+        if advance & 1 != 0 {
+            seed = seed.wrapping_mul(mult).wrapping_add(add);
+        }
+        add = add.wrapping_mul(mult.wrapping_add(1));
+        mult = mult.wrapping_mul(mult);
+        advance >>= 1;
+    }
+    seed
+}
+
+// seed = seed * MULT + ADD (mod 2 ** 64), outputs being the high half
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Lcg64<const MULT: u64, const ADD: u64> {
+    #[serde(serialize_with = "serialize_hex")]
+    pub seed: u64,
+}
+
+// gen 5
+pub type BwRng = Lcg64<0x5D588B656C078965, 0x269EC3>;
+
+const fn inverse_u64(mult: u64) -> u64 {
+    let mut inverse = mult;
+    let mut i = 0;
+    while i < 6 {
+        inverse = inverse.wrapping_mul(2u64.wrapping_sub(mult.wrapping_mul(inverse)));
+        i += 1;
+    }
+    inverse
+}
+
+impl<const MULT: u64, const ADD: u64> fmt::Display for Lcg64<MULT, ADD> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format_words(f, &[self.seed])
+    }
+}
+
+impl<const MULT: u64, const ADD: u64> FromStr for Lcg64<MULT, ADD> {
+    type Err = RngError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: [u64; 1] = parse_words(s)?;
+        Ok(Lcg64::new(words[0]))
+    }
+}
+
+impl<const MULT: u64, const ADD: u64> Lcg64<MULT, ADD> {
+    pub const REVERSE_MULT: u64 = inverse_u64(MULT);
+    pub const REVERSE_ADD: u64 = Self::REVERSE_MULT.wrapping_mul(ADD).wrapping_neg();
+
+    pub fn new(seed: u64) -> Self {
+        Lcg64 { seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.seed = self.seed.wrapping_mul(MULT).wrapping_add(ADD);
+        self.seed
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    // value in 0..max from the high half
+    pub fn next_max(&mut self, max: u32) -> u32 {
+        ((self.next_u32() as u64 * max as u64) >> 32) as u32
+    }
+
+    pub fn previous_u64(&mut self) -> u64 {
+        let seed = self.seed;
+        self.seed = self
+            .seed
+            .wrapping_mul(Self::REVERSE_MULT)
+            .wrapping_add(Self::REVERSE_ADD);
+        seed
+    }
+
+    pub fn advance(&mut self, advance: u64) {
+        self.seed = jump_affine_u64(self.seed, MULT, ADD, advance);
+    }
+
+    pub fn reverse(&mut self, advance: u64) {
+        self.seed = jump_affine_u64(self.seed, Self::REVERSE_MULT, Self::REVERSE_ADD, advance);
+    }
+}
+
+fn jump_affine_u64(seed: u64, mult: u64, add: u64, advance: u64) -> u64 {
+    let (mut mult, mut add) = (mult, add);
+    let mut seed = seed;
+    let mut advance = advance;
+    while advance != 0 {
+        if advance & 1 != 0 {
+            seed = seed.wrapping_mul(mult).wrapping_add(add);
+        }
+        add = add.wrapping_mul(mult.wrapping_add(1));
+        mult = mult.wrapping_mul(mult);
+        advance >>= 1;
+    }
+    seed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lcgs_match_known_sequences() {
+        let mut rng = PokeRng::new(0);
+        let seeds: Vec<u32> = (0..5).map(|_| rng.next_u32()).collect();
+        assert_eq!(
+            seeds,
+            [0x00006073, 0xE97E7B6A, 0x52713895, 0x31B0DDE4, 0x8E425287]
+        );
+        assert_eq!(XdRng::new(0).next_u32(), 0x00269EC3);
+        let mut rng = BwRng::new(0x0123456789ABCDEF);
+        assert_eq!(rng.next_u64(), 0x1DB90D64B7ACC50E);
+        assert_eq!(rng.next_u32(), 0xB6637069);
+        // 0xC971BCEF / 2 ** 32 of the way through 0..100
+        assert_eq!(rng.next_max(100), 78);
+    }
+
+    #[test]
+    fn lcgs_step_back_and_measure_distances() {
+        let mut rng = PokeRng::new(0x8E425287);
+        assert_eq!(rng.previous_u32(), 0x8E425287);
+        assert_eq!(rng.previous_u16(), 0x31B0);
+        assert_eq!(rng.seed, 0x52713895);
+        rng.reverse(2);
+        assert_eq!(rng.seed, 0x00006073);
+
+        let origin = PokeRng::new(0x12345678);
+        for advance in [0, 1, 1000, 0xFFFF_FFFF] {
+            let mut target = origin;
+            target.advance(advance);
+            assert_eq!(origin.distance(target), advance);
+            target.reverse(advance);
+            assert_eq!(target, origin);
+        }
+
+        let mut rng = BwRng::new(0x0123456789ABCDEF);
+        rng.advance(1 << 40);
+        rng.previous_u64();
+        rng.reverse((1 << 40) - 1);
+        assert_eq!(rng.seed, 0x0123456789ABCDEF);
+    }
+}
@@ -0,0 +1,31 @@
+pub mod berkowitz;
+pub mod berlekamp_massey;
+pub mod datetime;
+pub mod error;
+pub mod export;
+pub mod gen3;
+pub mod gen4;
+pub mod gen5;
+pub mod gf2_system;
+pub mod gf2int;
+pub mod gf2vec;
+pub mod jump;
+pub mod lcg;
+pub mod mat_builder;
+pub mod modarith;
+pub mod mt;
+pub mod notation;
+pub mod observation;
+pub mod pla;
+pub mod pohlig_hellman;
+pub mod pokemon;
+pub mod poly_analysis;
+pub mod poly_factor;
+pub mod progress;
+pub mod rng;
+pub mod search;
+pub mod sv;
+pub mod swsh;
+pub mod symbolic;
+pub mod xoroshiro;
+pub mod xoshiro;
@@ -1,14 +1,5 @@
-mod berkowitz;
-mod gf2int;
-mod gf2vec;
-mod mat_builder;
-mod pohlig_hellman;
-mod rng;
-mod xoroshiro;
-
-use crate::xoroshiro::Xoroshiro128Plus;
 use rand::{thread_rng, Rng};
-use rng::RNG;
+use rust_rng::{rng::RNG, xoroshiro::Xoroshiro128Plus};
 use std::time::Instant;
 
 fn main() {
@@ -23,7 +14,7 @@ fn main() {
             rng_test.jump(adv);
             let start = Instant::now();
 
-            println!("{}", rng.distance(rng_test));
+            println!("{:?}", rng.try_distance(rng_test));
 
             let duration = start.elapsed();
             println!("Task took {:?}", duration);
@@ -1,26 +1,64 @@
-use crate::gf2int::GF2Int;
+use crate::{error::RngError, gf2int::GF2Int};
 use ndarray::{Array2, Axis, Slice};
 use num_traits::One;
-use std::ops::{BitXor, BitXorAssign, Shl};
+use std::ops::{BitAnd, BitXor, BitXorAssign, Shl, Shr};
 
-pub fn mat_shl(n: usize) -> Array2<GF2Int> {
-    let mut eye = Array2::<GF2Int>::zeros((64, 64));
-    for i in n..64 {
+pub fn mat_shl(n: usize, word_size: usize) -> Array2<GF2Int> {
+    let mut eye = Array2::<GF2Int>::zeros((word_size, word_size));
+    for i in n..word_size {
         eye[[i - n, i]] = GF2Int::one();
     }
     eye
 }
 
-pub fn mat_rotl(n: usize) -> Array2<GF2Int> {
-    let mut eye = Array2::<GF2Int>::zeros((64, 64));
-    for i in 0..64 {
-        eye[[i, (i + n) % 64]] = GF2Int::one();
+pub fn mat_shr(n: usize, word_size: usize) -> Array2<GF2Int> {
+    let mut eye = Array2::<GF2Int>::zeros((word_size, word_size));
+    for i in n..word_size {
+        eye[[i, i - n]] = GF2Int::one();
     }
     eye
 }
 
-pub trait MatInverse {
+pub fn mat_rotl(n: usize, word_size: usize) -> Array2<GF2Int> {
+    let mut eye = Array2::<GF2Int>::zeros((word_size, word_size));
+    for i in 0..word_size {
+        eye[[i, (i + n) % word_size]] = GF2Int::one();
+    }
+    eye
+}
+
+pub fn mat_rotr(n: usize, word_size: usize) -> Array2<GF2Int> {
+    mat_rotl(word_size - n % word_size, word_size)
+}
+
+pub fn mat_mask(mask: u64, word_size: usize) -> Array2<GF2Int> {
+    let mut eye = Array2::<GF2Int>::zeros((word_size, word_size));
+    for i in 0..word_size {
+        eye[[i, i]] = GF2Int::new((mask >> i) as u8);
+    }
+    eye
+}
+
+pub fn mat_pow(matrix: &Array2<GF2Int>, power: u128) -> Array2<GF2Int> {
+    let mut power = power;
+    let mut base = matrix.to_owned();
+    let mut result = Array2::<GF2Int>::eye(matrix.shape()[0]);
+    // exponentiation by squares
+    while power > 0 {
+        if (power & 1) == 1 {
+            result = result.dot(&base);
+        }
+        power >>= 1;
+        if power > 0 {
+            base = base.dot(&base);
+        }
+    }
+    result
+}
+
+pub trait MatInverse: Sized {
     fn inverse(self) -> Self;
+    fn try_inverse(self) -> Result<Self, RngError>;
 }
 
 impl MatInverse for Array2<GF2Int> {
@@ -87,8 +125,28 @@ impl MatInverse for Array2<GF2Int> {
         }
         res
     }
+
+    // singular matrices come back from ``inverse`` as a non-inverse, so check,
+    // and non-square ones would index out of bounds in it
+    fn try_inverse(self) -> Result<Self, RngError> {
+        if !self.is_square() {
+            return Err(RngError::NotInvertible);
+        }
+        let inverse = self.clone().inverse();
+        let product = self.dot(&inverse);
+        let is_identity = product
+            .indexed_iter()
+            .all(|((i, j), bit)| bit.val == (i == j) as u8);
+        if is_identity {
+            Ok(inverse)
+        } else {
+            Err(RngError::NotInvertible)
+        }
+    }
 }
 
+// symbolic word of ``word_size`` bits, column i holds the state bits that
+// are xored together to produce bit i of the word
 pub struct MatBuilder {
     pub inner_matrix: Array2<GF2Int>,
 }
@@ -106,17 +164,51 @@ impl MatBuilder {
         MatBuilder { inner_matrix }
     }
 
+    pub fn word_size(&self) -> usize {
+        self.inner_matrix.shape()[1]
+    }
+
     pub fn rotate_left_assign(&mut self, rhs: usize) {
-        self.inner_matrix = self.inner_matrix.dot(&mat_rotl(rhs));
+        self.inner_matrix = self.inner_matrix.dot(&mat_rotl(rhs, self.word_size()));
     }
 
     pub fn rotate_left(&mut self, rhs: usize) -> Self {
         MatBuilder {
-            inner_matrix: self.inner_matrix.dot(&mat_rotl(rhs)),
+            inner_matrix: self.inner_matrix.dot(&mat_rotl(rhs, self.word_size())),
+        }
+    }
+
+    pub fn rotate_right_assign(&mut self, rhs: usize) {
+        self.inner_matrix = self.inner_matrix.dot(&mat_rotr(rhs, self.word_size()));
+    }
+
+    pub fn rotate_right(&mut self, rhs: usize) -> Self {
+        MatBuilder {
+            inner_matrix: self.inner_matrix.dot(&mat_rotr(rhs, self.word_size())),
+        }
+    }
+
+    // ``if word >> bit & 1 { mask } else { 0 }``
+    pub fn select_bit(&self, bit: usize, mask: u64) -> Self {
+        let mut inner_matrix = Array2::<GF2Int>::zeros(self.inner_matrix.dim());
+        for i in 0..self.word_size() {
+            if (mask >> i) & 1 != 0 {
+                inner_matrix
+                    .column_mut(i)
+                    .assign(&self.inner_matrix.column(bit));
+            }
+        }
+        MatBuilder { inner_matrix }
+    }
+
+    // multiply the word by a constant ``word_size`` x ``word_size`` matrix
+    pub fn dot(&self, rhs: &Array2<GF2Int>) -> Self {
+        MatBuilder {
+            inner_matrix: self.inner_matrix.dot(rhs),
         }
     }
 
-    pub fn to_owned(&mut self) -> Self {
+    pub fn to_owned(&self) -> Self {
         MatBuilder {
             inner_matrix: self.inner_matrix.to_owned(),
         }
@@ -124,6 +216,7 @@ impl MatBuilder {
 }
 
 impl BitXorAssign for MatBuilder {
+    #[allow(clippy::suspicious_op_assign_impl)]
     fn bitxor_assign(&mut self, rhs: Self) {
         // + == ^ under GF2, ArrayBase doesnt impl element-wise xor
         self.inner_matrix = self.inner_matrix.to_owned() + rhs.inner_matrix;
@@ -131,6 +224,7 @@ impl BitXorAssign for MatBuilder {
 }
 impl BitXor for MatBuilder {
     type Output = MatBuilder;
+    #[allow(clippy::suspicious_arithmetic_impl)]
     fn bitxor(self, rhs: Self) -> Self {
         // + == ^ under GF2, ArrayBase doesnt impl element-wise xor
         MatBuilder {
@@ -142,7 +236,94 @@ impl Shl<usize> for MatBuilder {
     type Output = MatBuilder;
     fn shl(self, rhs: usize) -> Self {
         MatBuilder {
-            inner_matrix: self.inner_matrix.dot(&mat_shl(rhs)),
+            inner_matrix: self.inner_matrix.dot(&mat_shl(rhs, self.word_size())),
+        }
+    }
+}
+impl Shr<usize> for MatBuilder {
+    type Output = MatBuilder;
+    fn shr(self, rhs: usize) -> Self {
+        MatBuilder {
+            inner_matrix: self.inner_matrix.dot(&mat_shr(rhs, self.word_size())),
+        }
+    }
+}
+impl BitAnd<u64> for MatBuilder {
+    type Output = MatBuilder;
+    fn bitand(self, rhs: u64) -> Self {
+        MatBuilder {
+            inner_matrix: self.inner_matrix.dot(&mat_mask(rhs, self.word_size())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::RngError, notation::format_matrix};
+
+    // the word ``builder`` computes from the state whose bit i is
+    // ``state >> i & 1``
+    fn evaluate(builder: &MatBuilder, state: u128) -> u64 {
+        let size = builder.inner_matrix.shape()[0];
+        let row = Array2::from_shape_fn((1, size), |(_, i)| GF2Int::new((state >> i) as u8 & 1));
+        row.dot(&builder.inner_matrix)
+            .iter()
+            .enumerate()
+            .map(|(i, bit)| (bit.val as u64) << i)
+            .fold(0, |word, bit| word | bit)
+    }
+
+    #[test]
+    fn builders_follow_word_operations_of_any_width() {
+        // a 32 bit word at bit 16 of a 96 bit state
+        let state: u128 = 0x0123_4567_89AB_CDEF_FEDC_BA98;
+        let word = (state >> 16) as u32;
+        let builder = MatBuilder::new(16, 32, 96);
+        assert_eq!(evaluate(&builder, state), word as u64);
+        let built = (builder.to_owned() << 7)
+            ^ builder.to_owned().rotate_left(13)
+            ^ ((builder.to_owned() >> 3) & 0x0F0F_F0F0);
+        let expected = (word << 7) ^ word.rotate_left(13) ^ ((word >> 3) & 0x0F0F_F0F0);
+        assert_eq!(evaluate(&built, state), expected as u64);
+
+        // 16 bit words at either end of a 32 bit state
+        let state: u128 = 0xBEEF_8001;
+        let mut low = MatBuilder::new(0, 16, 32);
+        low.rotate_right_assign(5);
+        let high = MatBuilder::new(16, 16, 32).select_bit(15, 0x1234);
+        assert_eq!(evaluate(&low, state), 0x8001u16.rotate_right(5) as u64);
+        assert_eq!(evaluate(&high, state), 0x1234);
+        assert_eq!(
+            evaluate(&(low ^ high), state),
+            (0x8001u16.rotate_right(5) ^ 0x1234) as u64
+        );
+
+        // a full 64 bit word is still the default case
+        let builder = MatBuilder::new(64, 64, 128).rotate_left(17);
+        let state = 0xDEAD_BEEF_0123_4567_89AB_CDEF_0000_0001u128;
+        assert_eq!(
+            evaluate(&builder, state),
+            ((state >> 64) as u64).rotate_left(17)
+        );
+    }
+
+    #[test]
+    fn try_inverse_rejects_singular_and_non_square_matrices() {
+        let rotation = mat_rotl(3, 8);
+        let inverse = rotation.clone().try_inverse().unwrap();
+        assert_eq!(
+            format_matrix(&rotation.dot(&inverse)),
+            format_matrix(&Array2::<GF2Int>::eye(8))
+        );
+        assert_eq!(format_matrix(&inverse), format_matrix(&mat_rotr(3, 8)));
+
+        for matrix in [
+            mat_shl(1, 8),
+            Array2::<GF2Int>::zeros((4, 4)),
+            Array2::<GF2Int>::zeros((3, 5)),
+        ] {
+            assert!(matches!(matrix.try_inverse(), Err(RngError::NotInvertible)));
         }
     }
 }
@@ -0,0 +1,528 @@
+use num_bigint::{BigInt, BigUint, ToBigInt};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ModArithError {
+    ZeroModulus,
+    NotInvertible,
+    // indices of the first pair of moduli sharing a factor
+    NotCoprime { first: usize, second: usize },
+    // congruences with a common factor that disagree on it
+    Inconsistent,
+    LengthMismatch,
+    // the result does not fit the requested integer type
+    Overflow,
+}
+
+impl fmt::Display for ModArithError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModArithError::ZeroModulus => write!(f, "modulus is zero"),
+            ModArithError::NotInvertible => write!(f, "value is not invertible"),
+            ModArithError::NotCoprime { first, second } => {
+                write!(f, "moduli {first} and {second} are not coprime")
+            }
+            ModArithError::Inconsistent => write!(f, "congruences are inconsistent"),
+            ModArithError::LengthMismatch => {
+                write!(f, "remainders and moduli differ in length")
+            }
+            ModArithError::Overflow => write!(f, "result overflows"),
+        }
+    }
+}
+
+impl std::error::Error for ModArithError {}
+
+pub fn mul_mod(a: u128, b: u128, modulus: u128) -> Result<u128, ModArithError> {
+    check_modulus(modulus)?;
+    Ok(mul_mod_unchecked(a, b, modulus))
+}
+
+pub fn add_mod(a: u128, b: u128, modulus: u128) -> Result<u128, ModArithError> {
+    check_modulus(modulus)?;
+    Ok(add_mod_unchecked(a % modulus, b % modulus, modulus))
+}
+
+pub fn pow_mod(base: u128, power: u128, modulus: u128) -> Result<u128, ModArithError> {
+    check_modulus(modulus)?;
+    Ok(pow_mod_unchecked(base, power, modulus))
+}
+
+fn check_modulus(modulus: u128) -> Result<(), ModArithError> {
+    if modulus == 0 {
+        Err(ModArithError::ZeroModulus)
+    } else {
+        Ok(())
+    }
+}
+
+// the unchecked variants take a nonzero modulus, and ``add_mod_unchecked``
+// reduced operands
+fn mul_mod_unchecked(a: u128, b: u128, modulus: u128) -> u128 {
+    if modulus <= u64::MAX as u128 {
+        return (a % modulus) * (b % modulus) % modulus;
+    }
+    let mut a = a % modulus;
+    let mut b = b % modulus;
+    let mut result = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod_unchecked(result, a, modulus);
+        }
+        a = add_mod_unchecked(a, a, modulus);
+        b >>= 1;
+    }
+    result
+}
+
+fn add_mod_unchecked(a: u128, b: u128, modulus: u128) -> u128 {
+    if a >= modulus - b {
+        a - (modulus - b)
+    } else {
+        a + b
+    }
+}
+
+fn sub_mod_unchecked(a: u128, b: u128, modulus: u128) -> u128 {
+    add_mod_unchecked(a, (modulus - b) % modulus, modulus)
+}
+
+fn pow_mod_unchecked(base: u128, power: u128, modulus: u128) -> u128 {
+    let mut power = power;
+    let mut base = base % modulus;
+    let mut result = 1 % modulus;
+    while power > 0 {
+        if power & 1 == 1 {
+            result = mul_mod_unchecked(result, base, modulus);
+        }
+        base = mul_mod_unchecked(base, base, modulus);
+        power >>= 1;
+    }
+    result
+}
+
+// the first 13 primes are a proven set of miller-rabin witnesses below
+// 3317044064679887385961981 (sorenson and webster), past it the strong
+// lucas test completes baillie-psw, which has no known counterexample
+const WITNESSES: [u128; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+const WITNESSES_BOUND: u128 = 3317044064679887385961981;
+
+pub fn is_prime(n: u128) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in WITNESSES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    if n < WITNESSES_BOUND {
+        WITNESSES.iter().all(|&a| is_strong_probable_prime(n, a))
+    } else {
+        is_strong_probable_prime(n, 2) && is_strong_lucas_probable_prime(n)
+    }
+}
+
+// miller-rabin to base ``a`` for odd ``n``
+fn is_strong_probable_prime(n: u128, a: u128) -> bool {
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    let mut x = pow_mod_unchecked(a, d, n);
+    if x == 1 || x == n - 1 {
+        return true;
+    }
+    for _ in 1..s {
+        x = mul_mod_unchecked(x, x, n);
+        if x == n - 1 {
+            return true;
+        }
+    }
+    false
+}
+
+// jacobi symbol (a / n) for odd ``n``
+fn jacobi(a: u128, n: u128) -> i32 {
+    let (mut a, mut n) = (a % n, n);
+    let mut result = 1;
+    while a != 0 {
+        while a.is_multiple_of(2) {
+            a /= 2;
+            if n % 8 == 3 || n % 8 == 5 {
+                result = -result;
+            }
+        }
+        (a, n) = (n, a);
+        if a % 4 == 3 && n % 4 == 3 {
+            result = -result;
+        }
+        a %= n;
+    }
+    if n == 1 {
+        result
+    } else {
+        0
+    }
+}
+
+// strong lucas test with selfridge's parameters, P = 1 and Q = (1 - D) / 4
+// for the first D of 5, -7, 9, -11, ... with (D / n) = -1, ``n`` being odd
+// and free of the witness primes
+fn is_strong_lucas_probable_prime(n: u128) -> bool {
+    // no such D exists for squares
+    if n.isqrt() * n.isqrt() == n {
+        return false;
+    }
+    let mut d: i128 = 5;
+    let reduce = |value: i128| {
+        let magnitude = value.unsigned_abs() % n;
+        if value < 0 {
+            (n - magnitude) % n
+        } else {
+            magnitude
+        }
+    };
+    loop {
+        match jacobi(reduce(d), n) {
+            -1 => break,
+            // |D| shares a factor with n, n being larger than any |D| reached
+            0 => return false,
+            _ => d = if d > 0 { -d - 2 } else { -d + 2 },
+        }
+    }
+    let d_mod = reduce(d);
+    let q = reduce((1 - d) / 4);
+    // x / 2 for odd n, adding n to odd x without overflowing
+    let half = |x: u128| {
+        if x.is_multiple_of(2) {
+            x / 2
+        } else {
+            x / 2 + n / 2 + 1
+        }
+    };
+
+    // n + 1 = k * 2 ** s, n + 1 fitting as u128::MAX is divisible by 3
+    let s = (n + 1).trailing_zeros();
+    let k = (n + 1) >> s;
+    // U_m, V_m and Q ** m from m = 1, doubling and stepping along k's bits
+    let (mut u, mut v, mut q_m) = (1, 1, q);
+    for bit in (0..127 - k.leading_zeros()).rev() {
+        u = mul_mod_unchecked(u, v, n);
+        v = sub_mod_unchecked(
+            mul_mod_unchecked(v, v, n),
+            add_mod_unchecked(q_m, q_m, n),
+            n,
+        );
+        q_m = mul_mod_unchecked(q_m, q_m, n);
+        if (k >> bit) & 1 == 1 {
+            (u, v) = (
+                half(add_mod_unchecked(u, v, n)),
+                half(add_mod_unchecked(mul_mod_unchecked(d_mod, u, n), v, n)),
+            );
+            q_m = mul_mod_unchecked(q_m, q, n);
+        }
+    }
+    if u == 0 || v == 0 {
+        return true;
+    }
+    for _ in 1..s {
+        v = sub_mod_unchecked(
+            mul_mod_unchecked(v, v, n),
+            add_mod_unchecked(q_m, q_m, n),
+            n,
+        );
+        q_m = mul_mod_unchecked(q_m, q_m, n);
+        if v == 0 {
+            return true;
+        }
+    }
+    false
+}
+
+// brent's variant of pollard's rho, ``n`` must be composite
+fn pollard_rho(n: u128) -> u128 {
+    if n.is_multiple_of(2) {
+        return 2;
+    }
+    for c in 1.. {
+        let f = |x: u128| add_mod_unchecked(mul_mod_unchecked(x, x, n), c % n, n);
+        let mut x = 2;
+        let mut y = 2;
+        let mut d = 1;
+        while d == 1 {
+            x = f(x);
+            y = f(f(y));
+            d = x.abs_diff(y).gcd(&n);
+        }
+        if d != n {
+            return d;
+        }
+    }
+    unreachable!()
+}
+
+// prime factors of ``n`` with their multiplicities, none for 0 and 1
+pub fn prime_factors(n: u128) -> Vec<(u128, u32)> {
+    if n == 0 {
+        return vec![];
+    }
+    let mut n = n;
+    let mut primes: Vec<u128> = vec![];
+    for p in 2..1 << 12 {
+        while n.is_multiple_of(p) {
+            primes.push(p);
+            n /= p;
+        }
+    }
+    let mut pending = if n > 1 { vec![n] } else { vec![] };
+    while let Some(n) = pending.pop() {
+        if is_prime(n) {
+            primes.push(n);
+        } else {
+            let d = pollard_rho(n);
+            pending.push(d);
+            pending.push(n / d);
+        }
+    }
+    primes.sort();
+    let mut factors: Vec<(u128, u32)> = vec![];
+    for p in primes {
+        match factors.last_mut() {
+            Some((last, count)) if *last == p => *count += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    factors
+}
+
+// a ** -1 mod m
+pub fn mod_inverse(a: &BigUint, m: &BigUint) -> Result<BigUint, ModArithError> {
+    if m.is_zero() {
+        return Err(ModArithError::ZeroModulus);
+    }
+    let a = BigInt::from(a % m);
+    let m = BigInt::from(m.clone());
+    let extended = a.extended_gcd(&m);
+    if !extended.gcd.is_one() {
+        return Err(ModArithError::NotInvertible);
+    }
+    Ok(extended.x.mod_floor(&m).to_biguint().unwrap())
+}
+
+pub fn mod_inverse_u128(a: u128, m: u128) -> Result<u128, ModArithError> {
+    mod_inverse(&BigUint::from(a), &BigUint::from(m))?
+        .try_into()
+        .map_err(|_| ModArithError::Overflow)
+}
+
+fn check_coprime(moduli: &[BigUint]) -> Result<(), ModArithError> {
+    // before the gcds, as gcd(m, 0) = m would report a zero as a shared factor
+    if moduli.iter().any(Zero::is_zero) {
+        return Err(ModArithError::ZeroModulus);
+    }
+    for (first, a) in moduli.iter().enumerate() {
+        for (second, b) in moduli.iter().enumerate().skip(first + 1) {
+            if !a.gcd(b).is_one() {
+                return Err(ModArithError::NotCoprime { first, second });
+            }
+        }
+    }
+    Ok(())
+}
+
+// (x, product of moduli) with x ≡ remainders[i] (mod moduli[i]) for pairwise
+// coprime moduli, by garner's mixed radix digits
+// x = v_0 + v_1 * m_0 + v_2 * m_0 * m_1 + ...
+pub fn crt(
+    remainders: &[BigUint],
+    moduli: &[BigUint],
+) -> Result<(BigUint, BigUint), ModArithError> {
+    if remainders.len() != moduli.len() {
+        return Err(ModArithError::LengthMismatch);
+    }
+    check_coprime(moduli)?;
+    let mut digits: Vec<BigUint> = vec![];
+    for (remainder, modulus) in remainders.iter().zip(moduli) {
+        let mut digit = BigInt::from(remainder % modulus);
+        let signed_modulus = BigInt::from(modulus.clone());
+        for (j, previous) in digits.iter().enumerate() {
+            let inverse = mod_inverse(&moduli[j], modulus)?;
+            digit = ((digit - BigInt::from(previous.clone())) * BigInt::from(inverse))
+                .mod_floor(&signed_modulus);
+        }
+        digits.push(digit.to_biguint().unwrap());
+    }
+    let mut result = BigUint::zero();
+    let mut radix = BigUint::one();
+    for (digit, modulus) in digits.iter().zip(moduli) {
+        result += digit * &radix;
+        radix *= modulus;
+    }
+    Ok((result, radix))
+}
+
+// ``crt`` on u128s, the product of the moduli may exceed u128 as long as
+// the solution does not
+pub fn crt_u128(remainders: &[u128], moduli: &[u128]) -> Result<u128, ModArithError> {
+    let remainders: Vec<BigUint> = remainders.iter().map(|&r| BigUint::from(r)).collect();
+    let moduli: Vec<BigUint> = moduli.iter().map(|&m| BigUint::from(m)).collect();
+    let (result, _) = crt(&remainders, &moduli)?;
+    result.try_into().map_err(|_| ModArithError::Overflow)
+}
+
+// combine (remainder, modulus) congruences whose moduli need not be coprime
+// into (remainder, lcm of the moduli)
+pub fn crt_general(congruences: &[(u128, u128)]) -> Result<(u128, u128), ModArithError> {
+    let mut remainder = BigInt::zero();
+    let mut modulus = BigInt::one();
+    for (rem, m) in congruences {
+        if *m == 0 {
+            return Err(ModArithError::ZeroModulus);
+        }
+        let rem = rem.to_bigint().unwrap();
+        let m = m.to_bigint().unwrap();
+        let extended = modulus.extended_gcd(&m);
+        let g = extended.gcd;
+        let diff = &rem - &remainder;
+        if !(&diff % &g).is_zero() {
+            return Err(ModArithError::Inconsistent);
+        }
+        let lcm = &modulus / &g * &m;
+        let step = (&diff / &g * extended.x).mod_floor(&(&m / &g));
+        remainder = (remainder + &modulus * step).mod_floor(&lcm);
+        modulus = lcm;
+    }
+    Ok((
+        remainder.try_into().map_err(|_| ModArithError::Overflow)?,
+        modulus.try_into().map_err(|_| ModArithError::Overflow)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MERSENNE_127: u128 = u128::MAX >> 1;
+
+    fn big(values: &[u128]) -> Vec<BigUint> {
+        values.iter().map(|&value| BigUint::from(value)).collect()
+    }
+
+    #[test]
+    fn arithmetic_past_64_bits() {
+        // (-2) * (-2) and (-1) * (-1) without overflowing
+        assert_eq!(mul_mod(u128::MAX - 2, u128::MAX - 2, u128::MAX), Ok(4));
+        assert_eq!(
+            mul_mod(MERSENNE_127 - 1, MERSENNE_127 - 1, MERSENNE_127),
+            Ok(1)
+        );
+        assert_eq!(
+            add_mod(u128::MAX - 1, u128::MAX - 1, u128::MAX),
+            Ok(u128::MAX - 2)
+        );
+        assert_eq!(pow_mod(2, MERSENNE_127 - 1, MERSENNE_127), Ok(1));
+        assert_eq!(pow_mod(3, 200, 1000), Ok(1));
+        assert_eq!(pow_mod(3, 0, 1), Ok(0));
+        assert_eq!(mul_mod(3, 5, 0), Err(ModArithError::ZeroModulus));
+        assert_eq!(add_mod(3, 5, 0), Err(ModArithError::ZeroModulus));
+        assert_eq!(pow_mod(3, 5, 0), Err(ModArithError::ZeroModulus));
+
+        assert_eq!(prime_factors(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(prime_factors(0), vec![]);
+        assert_eq!(prime_factors(1), vec![]);
+        let factors: Vec<u128> = prime_factors(u128::MAX)
+            .iter()
+            .map(|&(prime, count)| {
+                assert_eq!(count, 1);
+                prime
+            })
+            .collect();
+        assert_eq!(
+            factors,
+            [3, 5, 17, 257, 641, 65537, 274177, 6700417, 67280421310721]
+        );
+    }
+
+    #[test]
+    fn primality_past_the_witness_bound() {
+        assert!(is_prime(2) && is_prime(41) && is_prime((1 << 61) - 1));
+        // mersenne primes past the bound, left to baillie-psw
+        for exponent in [89, 107, 127] {
+            assert!(is_prime(u128::MAX >> (128 - exponent)));
+        }
+        assert!(is_prime(u128::MAX - 158));
+        // a carmichael number, a strong pseudoprime to bases 2, 3, 5 and 7,
+        // and composites past the bound: a semiprime, a square and
+        // 2 ** 128 - 1 itself
+        for composite in [
+            0,
+            1,
+            561,
+            3215031751,
+            ((1 << 61) - 1) * ((1 << 31) - 1),
+            ((1 << 61) - 1) * ((1 << 61) - 1),
+            u128::MAX,
+        ] {
+            assert!(!is_prime(composite));
+        }
+        // 5459 = 53 * 103 is the smallest strong lucas pseudoprime and
+        // 2047 = 23 * 89 the smallest strong pseudoprime to base 2, neither
+        // passing both halves of baillie-psw
+        assert!(is_strong_lucas_probable_prime(5459) && !is_strong_probable_prime(5459, 2));
+        assert!(is_strong_probable_prime(2047, 2) && !is_strong_lucas_probable_prime(2047));
+    }
+
+    #[test]
+    fn inverses() {
+        assert_eq!(mod_inverse_u128(3, 7), Ok(5));
+        assert_eq!(mod_inverse_u128(10, 7), Ok(5));
+        assert_eq!(mod_inverse_u128(2, 4), Err(ModArithError::NotInvertible));
+        assert_eq!(mod_inverse_u128(2, 0), Err(ModArithError::ZeroModulus));
+        assert_eq!(mod_inverse_u128(2, MERSENNE_127), Ok(1 << 126));
+        // 2 ** 128 + 1 is too big for the result type only
+        let modulus = BigUint::from(u128::MAX) + 2u32;
+        let inverse = mod_inverse(&BigUint::from(3u32), &modulus).unwrap();
+        assert_eq!((inverse * 3u32) % &modulus, BigUint::one());
+    }
+
+    #[test]
+    fn chinese_remainders() {
+        assert_eq!(
+            crt(&big(&[2, 3, 2]), &big(&[3, 5, 7])),
+            Ok((BigUint::from(23u32), BigUint::from(105u32)))
+        );
+        assert_eq!(
+            crt(&big(&[1, 2, 3]), &big(&[4, 9, 6])),
+            Err(ModArithError::NotCoprime {
+                first: 0,
+                second: 2
+            })
+        );
+        assert_eq!(
+            crt(&big(&[1, 2]), &big(&[3, 0])),
+            Err(ModArithError::ZeroModulus)
+        );
+        assert_eq!(
+            crt(&big(&[1]), &big(&[3, 5])),
+            Err(ModArithError::LengthMismatch)
+        );
+
+        // the moduli multiply past 2 ** 128 but the solution fits
+        let moduli = [MERSENNE_127, (1 << 61) - 1, 3];
+        assert_eq!(crt_u128(&[5, 5, 2], &moduli), Ok(5));
+        assert_eq!(crt_u128(&[5, 6, 2], &moduli), Err(ModArithError::Overflow));
+
+        assert_eq!(crt_general(&[(2, 4), (4, 6)]), Ok((10, 12)));
+        assert_eq!(crt_general(&[(3, 10), (3, 15), (0, 3)]), Ok((3, 30)));
+        assert_eq!(
+            crt_general(&[(1, 4), (2, 6)]),
+            Err(ModArithError::Inconsistent)
+        );
+        assert_eq!(
+            crt_general(&[(1, 4), (0, 0)]),
+            Err(ModArithError::ZeroModulus)
+        );
+        assert_eq!(crt_general(&[]), Ok((0, 1)));
+    }
+}
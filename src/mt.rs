@@ -0,0 +1,231 @@
+use crate::{
+    berkowitz::generator_minpoly_coeffs,
+    error::RngError,
+    export::serialize_hex_array,
+    gf2int::GF2Int,
+    gf2vec::GF2Vec19937,
+    jump::jump,
+    notation::{format_words, parse_words},
+    rng::RNG,
+};
+use ndarray::Array2;
+use num_bigint::BigUint;
+use serde::{Serialize, Serializer};
+use std::{fmt, str::FromStr, sync::OnceLock};
+
+// mt19937, https://www.math.sci.hiroshima-u.ac.jp/m-mat/MT/MT2002/CODES/mt19937ar.c
+const N: usize = 624;
+const M: usize = 397;
+const MATRIX_A: u32 = 0x9908B0DF;
+// only the top bit of the oldest word is part of the 19937-bit state
+const DEGREE: usize = 32 * N - 31;
+
+// the state is a ring buffer of the last N words, ``index`` being the oldest,
+// so each output twists a single word and states add word by word once
+// aligned on their oldest words
+#[derive(Clone, Debug)]
+pub struct Mt19937 {
+    state: [u32; N],
+    index: usize,
+}
+
+impl Default for Mt19937 {
+    fn default() -> Self {
+        Mt19937 {
+            state: [0; N],
+            index: 0,
+        }
+    }
+}
+
+impl Mt19937 {
+    pub fn new(seed: u32) -> Mt19937 {
+        let mut state = [0; N];
+        state[0] = seed;
+        for i in 1..N {
+            state[i] = 1812433253u32
+                .wrapping_mul(state[i - 1] ^ (state[i - 1] >> 30))
+                .wrapping_add(i as u32);
+        }
+        Mt19937 { state, index: 0 }
+    }
+
+    // the ``k``th oldest word
+    fn word(&self, k: usize) -> u32 {
+        self.state[(self.index + k) % N]
+    }
+
+    // the state words oldest first, independent of where the ring starts
+    pub fn words(&self) -> [u32; N] {
+        std::array::from_fn(|k| self.word(k))
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.next_state();
+        let mut y = self.word(N - 1);
+        y ^= y >> 11;
+        y ^= (y << 7) & 0x9D2C5680;
+        y ^= (y << 15) & 0xEFC60000;
+        y ^ (y >> 18)
+    }
+
+    // skips the tempering of the skipped outputs
+    pub fn advance(&mut self, advance: usize) {
+        for _ in 0..advance {
+            self.next_state();
+        }
+    }
+}
+
+// exported oldest word first so that equal states export equally
+#[derive(Serialize)]
+struct ExportedState {
+    #[serde(serialize_with = "serialize_hex_array")]
+    state: [u32; N],
+}
+
+impl Serialize for Mt19937 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ExportedState {
+            state: self.words(),
+        }
+        .serialize(serializer)
+    }
+}
+
+// the 624 words oldest first, parsing back into a ring starting at 0
+impl fmt::Display for Mt19937 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format_words(f, &self.words())
+    }
+}
+
+impl FromStr for Mt19937 {
+    type Err = RngError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Mt19937 {
+            state: parse_words(s)?,
+            index: 0,
+        })
+    }
+}
+
+// berkowitz is out of the question at 19937 bits, the characteristic
+// polynomial is primitive so it is also the minimal polynomial of the top bit
+// of the oldest word
+fn mt_charpoly() -> GF2Vec19937 {
+    static CHAR_POLY: OnceLock<GF2Vec19937> = OnceLock::new();
+    *CHAR_POLY.get_or_init(|| {
+        let mut coeffs = generator_minpoly_coeffs(&mut Mt19937::new(5489), DEGREE, |rng| {
+            GF2Int::new((rng.word(0) >> 31) as u8)
+        });
+        coeffs.reverse();
+        GF2Vec19937::new(coeffs)
+    })
+}
+
+impl RNG for Mt19937 {
+    type AdvanceInt = BigUint;
+    type MatrixInt = GF2Int;
+
+    fn next_state(&mut self) {
+        let y = (self.word(0) & 0x80000000) | (self.word(1) & 0x7FFFFFFF);
+        let mag = if y & 1 != 0 { MATRIX_A } else { 0 };
+        self.state[self.index] = self.word(M) ^ (y >> 1) ^ mag;
+        self.index = (self.index + 1) % N;
+    }
+
+    // over the words oldest first, the low 31 bits of the oldest word being
+    // dead coordinates; 32 * 624 squared entries, about 400 MB
+    fn matrix() -> Array2<Self::MatrixInt> {
+        let size = 32 * N;
+        let one = GF2Int::new(1);
+        let mut matrix = Array2::<GF2Int>::zeros((size, size));
+        for i in 32..size {
+            matrix[[i, i - 32]] = one;
+        }
+        let newest = 32 * (N - 1);
+        for bit in 0..32 {
+            matrix[[32 * M + bit, newest + bit]] = one;
+            // y >> 1, y being the top bit of word 0 over the low 31 of word 1
+            if bit == 30 {
+                matrix[[31, newest + bit]] = one;
+            } else if bit < 30 {
+                matrix[[32 + bit + 1, newest + bit]] = one;
+            }
+            if (MATRIX_A >> bit) & 1 != 0 {
+                matrix[[32, newest + bit]] = one;
+            }
+        }
+        matrix
+    }
+
+    fn advance(&mut self, adv: Self::AdvanceInt) {
+        let mut remaining = adv;
+        while remaining.bits() > 0 {
+            self.next_state();
+            remaining -= 1u32;
+        }
+    }
+
+    fn jump(&mut self, jmp: Self::AdvanceInt) {
+        jump(self, jmp, mt_charpoly());
+    }
+
+    // the group order 2 ** 19937 - 1 is a mersenne prime, leaving no
+    // subgroups for pohlig-hellman to split the discrete log into
+    fn try_distance(&mut self, mut other: Self) -> Result<Self::AdvanceInt, RngError> {
+        if self.state().bits() == 0 || other.state().bits() == 0 {
+            return Err(RngError::ZeroState);
+        }
+        Err(RngError::TooLarge)
+    }
+
+    fn state(&mut self) -> Self::AdvanceInt {
+        let mut words: Vec<u32> = (0..N).map(|k| self.word(k)).collect();
+        words[0] &= 0x80000000;
+        BigUint::from_slice(&words)
+    }
+
+    fn add_state(&mut self, other: &Self) {
+        for k in 0..N {
+            self.state[(self.index + k) % N] ^= other.word(k);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_u32_matches_reference() {
+        // mt19937ar.c seeded through init_genrand(5489)
+        let mut rng = Mt19937::new(5489);
+        let outputs: Vec<u32> = (0..3).map(|_| rng.next_u32()).collect();
+        assert_eq!(outputs, [3499211612, 581869302, 3890346734]);
+        rng.advance(9996);
+        // the 10000th output
+        assert_eq!(rng.next_u32(), 4123659995);
+    }
+
+    #[test]
+    fn charpoly_has_full_degree() {
+        assert_eq!(mt_charpoly().degree() as usize, DEGREE);
+    }
+
+    #[test]
+    fn distances_are_only_reported_as_errors() {
+        // even a single step is out of reach, the failure being the same for
+        // every pair of nonzero states
+        let mut rng = Mt19937::new(1);
+        let mut next = rng.clone();
+        next.next_state();
+        assert_eq!(rng.try_distance(next), Err(RngError::TooLarge));
+        assert_eq!(
+            rng.try_distance(Mt19937::default()),
+            Err(RngError::ZeroState)
+        );
+    }
+}
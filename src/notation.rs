@@ -0,0 +1,340 @@
+use crate::{error::RngError, gf2int::GF2Int, gf2vec::GF2Vec};
+use ndarray::Array2;
+use num_traits::Num;
+use std::{fmt, str::FromStr};
+
+fn parse_error(message: String) -> RngError {
+    RngError::Parse(message)
+}
+
+// ``x^128 + x^3 + x + 1``, highest power first
+impl<const LIMBS: usize> fmt::Display for GF2Vec<LIMBS> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let terms: Vec<String> = (0..=self.degree())
+            .rev()
+            .filter(|power| self.bit(*power))
+            .map(|power| match power {
+                0 => "1".to_string(),
+                1 => "x".to_string(),
+                _ => format!("x^{power}"),
+            })
+            .collect();
+        write!(f, "{}", terms.join(" + "))
+    }
+}
+
+// hex value of the coefficients, bit i being x ** i
+impl<const LIMBS: usize> fmt::LowerHex for GF2Vec<LIMBS> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let top = self.limbs.iter().rposition(|limb| *limb != 0).unwrap_or(0);
+        let mut hex = format!("{:x}", self.limbs[top]);
+        for limb in self.limbs[..top].iter().rev() {
+            hex.push_str(&format!("{:016x}", limb));
+        }
+        f.pad_integral(true, "0x", &hex)
+    }
+}
+
+impl<const LIMBS: usize> fmt::UpperHex for GF2Vec<LIMBS> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "0x", &format!("{self:x}").to_uppercase())
+    }
+}
+
+// either hex (``0x...``) or a sum of terms ``x^k``, ``x`` and ``1``, ``z`` being
+// accepted for ``x``; repeated terms cancel as they would under GF(2)
+impl<const LIMBS: usize> FromStr for GF2Vec<LIMBS> {
+    type Err = RngError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            if !hex.chars().any(|c| c != '_') {
+                return Err(parse_error(format!("{s} has no hex digits")));
+            }
+            let mut result = Self::ZERO;
+            for (i, digit) in hex.chars().filter(|c| *c != '_').rev().enumerate() {
+                let nibble = digit
+                    .to_digit(16)
+                    .ok_or_else(|| parse_error(format!("invalid hex digit {digit:?}")))?
+                    as u64;
+                if nibble != 0 && 4 * i as u32 >= Self::BITS {
+                    return Err(parse_error(format!(
+                        "{s} does not fit in {} bits",
+                        Self::BITS
+                    )));
+                }
+                if nibble != 0 {
+                    result.limbs[i / 16] |= nibble << (4 * (i % 16));
+                }
+            }
+            return Ok(result);
+        }
+        let mut result = Self::ZERO;
+        for term in s.split('+').map(str::trim) {
+            let power = match term {
+                "0" => continue,
+                "1" => 0,
+                "x" | "z" => 1,
+                _ => term
+                    .strip_prefix("x^")
+                    .or_else(|| term.strip_prefix("z^"))
+                    .or_else(|| term.strip_prefix("x**"))
+                    .or_else(|| term.strip_prefix("z**"))
+                    .and_then(|power| power.trim().parse::<u32>().ok())
+                    .ok_or_else(|| parse_error(format!("invalid term {term:?}")))?,
+            };
+            if power >= Self::BITS {
+                return Err(parse_error(format!(
+                    "x^{power} does not fit in {} bits",
+                    Self::BITS
+                )));
+            }
+            result ^= Self::ONE << power;
+        }
+        Ok(result)
+    }
+}
+
+pub fn format_row<'a>(row: impl IntoIterator<Item = &'a GF2Int>) -> String {
+    row.into_iter()
+        .map(|bit| if bit.val == 0 { '0' } else { '1' })
+        .collect()
+}
+
+// one row of 0/1 characters per line
+pub fn format_matrix(matrix: &Array2<GF2Int>) -> String {
+    matrix
+        .rows()
+        .into_iter()
+        .map(|row| format_row(row.iter()))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// inverse of ``format_matrix``, blank lines and whitespace within rows being
+// ignored
+pub fn parse_matrix(s: &str) -> Result<Array2<GF2Int>, RngError> {
+    let rows: Vec<Vec<GF2Int>> = s
+        .lines()
+        .map(|line| line.split_whitespace().collect::<String>())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.chars()
+                .map(|c| match c {
+                    '0' => Ok(GF2Int::new(0)),
+                    '1' => Ok(GF2Int::new(1)),
+                    _ => Err(parse_error(format!("invalid matrix entry {c:?}"))),
+                })
+                .collect()
+        })
+        .collect::<Result<_, _>>()?;
+    let columns = rows.first().map_or(0, |row| row.len());
+    if rows.iter().any(|row| row.len() != columns) {
+        return Err(parse_error("matrix rows differ in length".to_string()));
+    }
+    Array2::from_shape_vec((rows.len(), columns), rows.concat())
+        .map_err(|error| parse_error(error.to_string()))
+}
+
+// matrices for Display and FromStr, which ndarray's own impls stand in the
+// way of, in the notation of ``format_matrix``
+#[derive(Clone, Debug)]
+pub struct GF2Matrix(pub Array2<GF2Int>);
+
+impl fmt::Display for GF2Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", format_matrix(&self.0))
+    }
+}
+
+impl FromStr for GF2Matrix {
+    type Err = RngError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_matrix(s).map(GF2Matrix)
+    }
+}
+
+// generator states as their words in zero-padded hex, separated by ``:``
+pub fn format_words<T: fmt::UpperHex>(f: &mut fmt::Formatter, words: &[T]) -> fmt::Result {
+    let width = 2 + 2 * std::mem::size_of::<T>();
+    for (i, word) in words.iter().enumerate() {
+        if i != 0 {
+            write!(f, ":")?;
+        }
+        write!(f, "{word:#0width$X}")?;
+    }
+    Ok(())
+}
+
+// inverse of ``format_words``, the ``0x`` prefix being optional
+pub fn parse_words<T: Num, const N: usize>(s: &str) -> Result<[T; N], RngError> {
+    let words = s
+        .trim()
+        .split(':')
+        .map(|word| {
+            let word = word.trim();
+            let digits = word
+                .strip_prefix("0x")
+                .or_else(|| word.strip_prefix("0X"))
+                .unwrap_or(word);
+            T::from_str_radix(digits, 16)
+                .map_err(|_| parse_error(format!("invalid state word {word:?}")))
+        })
+        .collect::<Result<Vec<T>, _>>()?;
+    let count = words.len();
+    words
+        .try_into()
+        .map_err(|_| parse_error(format!("expected {N} state words, got {count}")))
+}
+
+// Display and FromStr through ``format_words``/``parse_words``, for states
+// held in named word fields or in a ``seed`` array passed to ``new``
+macro_rules! impl_state_notation {
+    ($rng:ident { $($field:ident),+ }: $word:ty) => {
+        impl std::fmt::Display for $rng {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                $crate::notation::format_words(f, &[$(self.$field),+])
+            }
+        }
+
+        impl std::str::FromStr for $rng {
+            type Err = $crate::error::RngError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let [$($field),+]: [$word; [$(stringify!($field)),+].len()] =
+                    $crate::notation::parse_words(s)?;
+                Ok($rng { $($field),+ })
+            }
+        }
+    };
+    ($rng:ident [$word:ty; $count:literal]) => {
+        impl std::fmt::Display for $rng {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                $crate::notation::format_words(f, &self.seed)
+            }
+        }
+
+        impl std::str::FromStr for $rng {
+            type Err = $crate::error::RngError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let words: [$word; $count] = $crate::notation::parse_words(s)?;
+                Ok($rng::new(words))
+            }
+        }
+    };
+}
+
+pub(crate) use impl_state_notation;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gf2vec::{GF2Vec256, GF2Vec64},
+        lcg::{BwRng, PokeRng},
+        mt::Mt19937,
+        rng::RNG,
+        xoroshiro::{
+            Xoroshiro128Plus, Xoroshiro128PlusPlus, Xoroshiro128StarStar, Xoroshiro64Star,
+        },
+        xoshiro::{Xoshiro256Plus, Xoshiro256PlusPlus, Xoshiro256StarStar},
+    };
+
+    fn round_trip<T: fmt::Display + FromStr<Err = RngError>>(value: &T) -> String {
+        let text = value.to_string();
+        let parsed: T = text.parse().unwrap();
+        assert_eq!(parsed.to_string(), text);
+        text
+    }
+
+    #[test]
+    fn polynomials_round_trip() {
+        let poly = (GF2Vec256::ONE << 200) + (GF2Vec256::ONE << 64) + GF2Vec256::from(0b1011u64);
+        assert_eq!(round_trip(&poly), "x^200 + x^64 + x^3 + x + 1");
+        let hex = format!("{poly:#x}");
+        assert_eq!(hex, "0x10000000000000000000000000000000001000000000000000b");
+        assert_eq!(hex.parse::<GF2Vec256>(), Ok(poly));
+        assert_eq!("z**200 + z^64 + x^3 + x + x + x + 1".parse(), Ok(poly));
+        assert_eq!(round_trip(&GF2Vec64::ZERO), "0");
+        assert_eq!("0x0".parse(), Ok(GF2Vec64::ZERO));
+
+        for text in ["0x", "0X__", "0xg", "x^128", "x^2 + y"] {
+            assert!(matches!(text.parse::<GF2Vec64>(), Err(RngError::Parse(_))));
+        }
+    }
+
+    #[test]
+    fn states_round_trip() {
+        assert_eq!(
+            round_trip(&Xoroshiro128Plus::new(0x1234)),
+            "0x0000000000001234:0x82A2B175229D6A5B"
+        );
+        round_trip(&Xoroshiro128PlusPlus::new(1, u64::MAX));
+        round_trip(&Xoroshiro128StarStar::new(u64::MAX, 1));
+        assert_eq!(
+            round_trip(&Xoroshiro64Star::new(1, 0xABCDEF)),
+            "0x00000001:0x00ABCDEF"
+        );
+        round_trip(&Xoshiro256Plus::new([1, 2, 3, 4]));
+        round_trip(&Xoshiro256PlusPlus::new([u64::MAX, 0, 1, 2]));
+        round_trip(&Xoshiro256StarStar::new([5, 6, 7, u64::MAX]));
+        assert_eq!(round_trip(&PokeRng::new(0x6073)), "0x00006073");
+        round_trip(&BwRng::new(0x0123456789ABCDEF));
+
+        assert_eq!(
+            "1234:82a2b175229d6a5b"
+                .parse::<Xoroshiro128Plus>()
+                .unwrap()
+                .to_string(),
+            "0x0000000000001234:0x82A2B175229D6A5B"
+        );
+        for text in ["1234", "1:2:3", "1:xyz", "100000000:1"] {
+            assert!(matches!(
+                text.parse::<Xoroshiro64Star>(),
+                Err(RngError::Parse(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn mt_states_round_trip() {
+        let mut rng = Mt19937::new(5489);
+        let text = round_trip(&rng);
+        assert_eq!(text.split(':').count(), 624);
+        assert!(text.starts_with("0x00001571:0x"));
+
+        // ring buffer not starting at its oldest word
+        rng.advance(1000);
+        let mut parsed: Mt19937 = round_trip(&rng).parse().unwrap();
+        for _ in 0..1000 {
+            assert_eq!(parsed.next_u32(), rng.next_u32());
+        }
+
+        assert!(matches!(
+            "0x1:0x2".parse::<Mt19937>(),
+            Err(RngError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn matrices_round_trip() {
+        let matrix = GF2Matrix(Xoroshiro64Star::matrix());
+        let text = round_trip(&matrix);
+        assert_eq!(text.lines().count(), 64);
+        assert_eq!(text, format_matrix(&matrix.0));
+        assert_eq!(
+            round_trip(&"01\n 1 0\n\n".parse::<GF2Matrix>().unwrap()),
+            "01\n10"
+        );
+
+        for text in ["012", "01\n1"] {
+            assert!(matches!(text.parse::<GF2Matrix>(), Err(RngError::Parse(_))));
+        }
+    }
+}
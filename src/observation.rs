@@ -0,0 +1,86 @@
+use crate::{gf2int::GF2Int, mat_builder::mat_pow, rng::RNG};
+use ndarray::{s, Array2};
+
+// linear map from the initial state to the requested output bits, one column
+// per bit in the order of ``observations``
+pub fn observation_matrix(
+    transition_matrix: &Array2<GF2Int>,
+    output_matrix: &Array2<GF2Int>,
+    observations: &[(u128, Vec<usize>)],
+) -> Array2<GF2Int> {
+    let state_size = transition_matrix.shape()[0];
+    let mut offsets = vec![0];
+    for (_, bits) in observations {
+        offsets.push(offsets.last().unwrap() + bits.len());
+    }
+    let mut result = Array2::<GF2Int>::zeros((state_size, *offsets.last().unwrap()));
+
+    // visit advances in increasing order so each power builds on the last
+    let mut order: Vec<usize> = (0..observations.len()).collect();
+    order.sort_by_key(|&i| observations[i].0);
+    let mut advance = 0;
+    let mut power = Array2::<GF2Int>::eye(state_size);
+    for i in order {
+        let (observation_advance, bits) = &observations[i];
+        if *observation_advance != advance {
+            power = power.dot(&mat_pow(transition_matrix, observation_advance - advance));
+            advance = *observation_advance;
+        }
+        let observed = power.dot(output_matrix);
+        for (j, bit) in bits.iter().enumerate() {
+            result
+                .slice_mut(s![.., offsets[i] + j])
+                .assign(&observed.slice(s![.., *bit]));
+        }
+    }
+    result
+}
+
+pub trait LinearOutput: RNG<MatrixInt = GF2Int> {
+    // (state size, output size) matrix of the output bits, only bits that
+    // are linear in the state are exact
+    fn output_matrix() -> Array2<GF2Int>;
+
+    fn observation_matrix(observations: &[(u128, Vec<usize>)]) -> Array2<GF2Int> {
+        observation_matrix(&Self::matrix(), &Self::output_matrix(), observations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xoroshiro::Xoroshiro128Plus;
+
+    #[test]
+    fn observations_follow_the_generator() {
+        let origin = Xoroshiro128Plus::new(0x0123456789ABCDEF);
+        // out of order and repeated advances keep their columns
+        let observations = [
+            (5, vec![0]),
+            (0, vec![0, 7]),
+            (40, vec![63, 0]),
+            (5, vec![1]),
+        ];
+        let matrix = Xoroshiro128Plus::observation_matrix(&observations);
+        assert_eq!(matrix.dim(), (128, 6));
+        let state = { origin }.state();
+        let row = Array2::from_shape_fn((1, 128), |(_, i)| GF2Int::new((state >> i) as u8 & 1));
+        let observed: Vec<u8> = row.dot(&matrix).iter().map(|bit| bit.val).collect();
+
+        // bit 0 of the real output, the other bits of s0 ^ s1
+        let expected: Vec<u8> = observations
+            .iter()
+            .flat_map(|(advance, bits)| {
+                let mut rng = origin;
+                rng.jump(*advance);
+                let linear = rng.seed_0 ^ rng.seed_1;
+                let output = rng.next_u64();
+                assert_eq!(output & 1, linear & 1);
+                bits.iter()
+                    .map(move |bit| (linear >> bit) as u8 & 1)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert_eq!(observed, expected);
+    }
+}
@@ -0,0 +1,324 @@
+use crate::{
+    export::serialize_hex,
+    pokemon::{
+        apply_trainer_shininess, roll_ivs, rolled_pid_mask, shiny_type, Gender, Pokemon, ShinyType,
+    },
+    progress::{uncancelled, Cancelled, ChunkCounter, Progress},
+    xoroshiro::Xoroshiro128Plus,
+};
+use rayon::prelude::*;
+use serde::Serialize;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Research {
+    Incomplete,
+    Complete,
+    Perfect,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Outbreak {
+    None,
+    Mass,
+    MassiveMass,
+}
+
+pub fn shiny_rolls(research: Research, shiny_charm: bool, outbreak: Outbreak) -> u8 {
+    let research = match research {
+        Research::Incomplete => 0,
+        Research::Complete => 1,
+        Research::Perfect => 3,
+    };
+    let outbreak = match outbreak {
+        Outbreak::None => 0,
+        Outbreak::Mass => 25,
+        Outbreak::MassiveMass => 12,
+    };
+    1 + research + if shiny_charm { 3 } else { 0 } + outbreak
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Encounter {
+    pub flawless_ivs: u8,
+    // 255 genderless, 254 female only, 0 male only
+    pub gender_ratio: u8,
+    pub shiny_rolls: u8,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub struct Spawn {
+    #[serde(serialize_with = "serialize_hex")]
+    pub generator_seed: u64,
+    // uniform in [0, 1), scaled by the total slot weight to pick the slot
+    pub slot_rand: f64,
+    #[serde(serialize_with = "serialize_hex")]
+    pub fixed_seed: u64,
+}
+
+pub fn group_seed(spawner_seed: u64) -> u64 {
+    Xoroshiro128Plus::new(spawner_seed).next_u64()
+}
+
+// top 53 bits over 2 ** 53, dividing the whole output by 2 ** 64 rounds
+// outputs close to 2 ** 64 up to 1.0
+fn unit_float(rand: u64) -> f64 {
+    (rand >> 11) as f64 * 2f64.powi(-53)
+}
+
+// the spawns of a group and the group seed of the following wave
+pub fn group_spawns(group_seed: u64, spawn_count: usize) -> (Vec<Spawn>, u64) {
+    let mut group_rng = Xoroshiro128Plus::new(group_seed);
+    let spawns = (0..spawn_count)
+        .map(|_| {
+            let generator_seed = group_rng.next_u64();
+            // second output is unused
+            group_rng.next_u64();
+            let mut generator_rng = Xoroshiro128Plus::new(generator_seed);
+            let slot_rand = unit_float(generator_rng.next_u64());
+            Spawn {
+                generator_seed,
+                slot_rand,
+                fixed_seed: generator_rng.next_u64(),
+            }
+        })
+        .collect();
+    (spawns, group_rng.next_u64())
+}
+
+// index of the slot picked by ``slot_rand`` out of the weighted slots
+pub fn slot(slot_rand: f64, weights: &[f64]) -> usize {
+    let mut target = slot_rand * weights.iter().sum::<f64>();
+    for (i, weight) in weights.iter().enumerate() {
+        if target < *weight {
+            return i;
+        }
+        target -= weight;
+    }
+    weights.len() - 1
+}
+
+pub fn generate_fixed(fixed_seed: u64, encounter: &Encounter, tid: u16, sid: u16) -> Pokemon {
+    let tidsid = ((sid as u32) << 16) | tid as u32;
+    let mut rng = Xoroshiro128Plus::new(fixed_seed);
+    let ec = rng.rand(0xFFFFFFFF) as u32;
+    let fake_tidsid = rng.rand(0xFFFFFFFF) as u32;
+    let mut pid = 0;
+    for _ in 0..encounter.shiny_rolls.max(1) {
+        pid = rng.rand(0xFFFFFFFF) as u32;
+        if shiny_type(pid, fake_tidsid) != ShinyType::None {
+            break;
+        }
+    }
+    let pid = apply_trainer_shininess(pid, fake_tidsid, tidsid);
+    let ivs = roll_ivs(encounter.flawless_ivs, |max| rng.rand(max));
+    let ability = rng.rand(2) as u8;
+    let gender = match encounter.gender_ratio {
+        255 => Gender::Genderless,
+        254 => Gender::Female,
+        0 => Gender::Male,
+        ratio => {
+            if (rng.rand(252) + 1) < ratio as u64 {
+                Gender::Female
+            } else {
+                Gender::Male
+            }
+        }
+    };
+    let nature = rng.rand(25) as u8;
+
+    Pokemon {
+        ec,
+        pid,
+        shiny: shiny_type(pid, tidsid),
+        ivs,
+        ability,
+        gender,
+        nature,
+    }
+}
+
+// fixed seeds generating ``pokemon``, trying every roll the pid may have come
+// from when it is shiny
+pub fn recover_fixed_seeds(
+    encounter: &Encounter,
+    tid: u16,
+    sid: u16,
+    pokemon: &Pokemon,
+) -> Vec<u64> {
+    uncancelled(|progress| {
+        recover_fixed_seeds_with_progress(encounter, tid, sid, pokemon, progress)
+    })
+}
+
+// each candidate seed is a chunk
+pub fn recover_fixed_seeds_with_progress(
+    encounter: &Encounter,
+    tid: u16,
+    sid: u16,
+    pokemon: &Pokemon,
+    progress: &Progress,
+) -> Result<Vec<u64>, Cancelled> {
+    let rolls = encounter.shiny_rolls.max(1) as u128;
+    let pid_advances = if pokemon.shiny == ShinyType::None {
+        rolls..=rolls
+    } else {
+        1..=rolls
+    };
+    let candidates: Vec<u64> = pid_advances
+        .flat_map(|roll| {
+            Xoroshiro128Plus::seeds_from_outputs(&[
+                (0, pokemon.ec as u64, 0xFFFFFFFF),
+                (1 + roll, pokemon.pid as u64, rolled_pid_mask(pokemon)),
+            ])
+        })
+        .collect();
+    let counter = ChunkCounter::new(progress, candidates.len() as u128);
+    let seeds: Vec<Option<u64>> = candidates
+        .into_par_iter()
+        .map(|seed| {
+            progress.check()?;
+            let matches = generate_fixed(seed, encounter, tid, sid) == *pokemon;
+            counter.finish();
+            Ok(matches.then_some(seed))
+        })
+        .collect::<Result<_, Cancelled>>()?;
+    let mut seeds: Vec<u64> = seeds.into_iter().flatten().collect();
+    seeds.sort_unstable();
+    seeds.dedup();
+    Ok(seeds)
+}
+
+// group seeds whose first spawn generates ``first`` and one of the following
+// ``spawn_count - 1`` spawns ``second``, the group seed being the generator
+// seed minus seed_1 as it is the first output of the group
+pub fn recover_group_seeds(
+    encounter: &Encounter,
+    tid: u16,
+    sid: u16,
+    first: &Pokemon,
+    second: &Pokemon,
+    spawn_count: usize,
+) -> Vec<u64> {
+    uncancelled(|progress| {
+        recover_group_seeds_with_progress(encounter, tid, sid, first, second, spawn_count, progress)
+    })
+}
+
+// the candidates of the fixed seeds and then of the group seeds are chunks
+pub fn recover_group_seeds_with_progress(
+    encounter: &Encounter,
+    tid: u16,
+    sid: u16,
+    first: &Pokemon,
+    second: &Pokemon,
+    spawn_count: usize,
+    progress: &Progress,
+) -> Result<Vec<u64>, Cancelled> {
+    let seed_1 = Xoroshiro128Plus::new(0).seed_1;
+    let candidates: Vec<u64> =
+        recover_fixed_seeds_with_progress(encounter, tid, sid, first, progress)?
+            .into_iter()
+            // the fixed seed is the second output of the generator
+            .flat_map(|fixed_seed| {
+                Xoroshiro128Plus::seeds_from_outputs(&[(1, fixed_seed, u64::MAX)])
+            })
+            .map(|generator_seed| generator_seed.wrapping_sub(seed_1))
+            .collect();
+    let counter = ChunkCounter::new(progress, candidates.len() as u128);
+    let mut seeds = vec![];
+    for group_seed in candidates {
+        progress.check()?;
+        let (spawns, _) = group_spawns(group_seed, spawn_count);
+        if spawns
+            .iter()
+            .skip(1)
+            .any(|spawn| generate_fixed(spawn.fixed_seed, encounter, tid, sid) == *second)
+        {
+            seeds.push(group_seed);
+        }
+        counter.finish();
+    }
+    seeds.sort_unstable();
+    seeds.dedup();
+    Ok(seeds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCOUNTER: Encounter = Encounter {
+        flawless_ivs: 0,
+        gender_ratio: 0x7F,
+        shiny_rolls: 4,
+    };
+
+    #[test]
+    fn shiny_rolls_and_slots() {
+        assert_eq!(shiny_rolls(Research::Incomplete, false, Outbreak::None), 1);
+        assert_eq!(shiny_rolls(Research::Perfect, true, Outbreak::None), 7);
+        assert_eq!(shiny_rolls(Research::Perfect, true, Outbreak::Mass), 32);
+        assert_eq!(
+            shiny_rolls(Research::Complete, false, Outbreak::MassiveMass),
+            14
+        );
+
+        let weights = [100.0, 20.0, 5.0];
+        assert_eq!(slot(0.0, &weights), 0);
+        assert_eq!(slot(0.79, &weights), 0);
+        assert_eq!(slot(0.81, &weights), 1);
+        assert_eq!(slot(0.97, &weights), 2);
+    }
+
+    #[test]
+    fn slot_rand_stays_below_one() {
+        assert_eq!(unit_float(0), 0.0);
+        assert_eq!(unit_float(1 << 63), 0.5);
+        assert_eq!(unit_float(u64::MAX), 1.0 - 2f64.powi(-53));
+        assert_eq!(unit_float(u64::MAX - (1 << 10)), 1.0 - 2f64.powi(-53));
+    }
+
+    #[test]
+    fn spawns_follow_the_group_seed() {
+        // the first output of any seed is the seed plus seed_1
+        let seed_1 = Xoroshiro128Plus::new(0).seed_1;
+        assert_eq!(group_seed(0x1234), 0x1234u64.wrapping_add(seed_1));
+
+        let (spawns, next) = group_spawns(0x0123456789ABCDEF, 2);
+        let mut rng = Xoroshiro128Plus::new(0x0123456789ABCDEF);
+        let first = rng.next_u64();
+        rng.next_u64();
+        let second = rng.next_u64();
+        rng.next_u64();
+        assert_eq!(next, rng.next_u64());
+        assert_eq!(
+            spawns
+                .iter()
+                .map(|spawn| spawn.generator_seed)
+                .collect::<Vec<_>>(),
+            [first, second]
+        );
+        let mut generator = Xoroshiro128Plus::new(first);
+        generator.next_u64();
+        assert_eq!(spawns[0].fixed_seed, generator.next_u64());
+        assert!((0.0..1.0).contains(&spawns[0].slot_rand));
+    }
+
+    #[test]
+    fn fixed_and_group_seeds_are_recovered() {
+        let fixed_seed = 0x0F1E2D3C4B5A6978;
+        let pokemon = generate_fixed(fixed_seed, &ENCOUNTER, 12345, 54321);
+        assert!(recover_fixed_seeds(&ENCOUNTER, 12345, 54321, &pokemon).contains(&fixed_seed));
+
+        let group_seed = 0xFEEDFACE12345678;
+        let (spawns, _) = group_spawns(group_seed, 4);
+        let first = generate_fixed(spawns[0].fixed_seed, &ENCOUNTER, 12345, 54321);
+        let third = generate_fixed(spawns[2].fixed_seed, &ENCOUNTER, 12345, 54321);
+        assert_eq!(
+            recover_group_seeds(&ENCOUNTER, 12345, 54321, &first, &third, 4),
+            vec![group_seed]
+        );
+        // the second pokemon must come from a later spawn of the group
+        assert!(recover_group_seeds(&ENCOUNTER, 12345, 54321, &first, &third, 2).is_empty());
+    }
+}
@@ -1,148 +1,389 @@
-use crate::gf2vec::{compute_jump_poly, GF2Vec128};
-use num_bigint::{BigInt, ToBigInt};
-use num_traits::{One, Zero};
+use crate::{
+    error::RngError,
+    gf2vec::{compute_jump_poly, Exponent, GF2Vec},
+    modarith::{crt, ModArithError},
+    progress::{Progress, ProgressEvent, CHECK_INTERVAL},
+};
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
 use rayon::{current_num_threads, prelude::*};
-use std::collections::HashSet;
-
-fn modular_inverse(a: &BigInt, m: &BigInt) -> BigInt {
-    let mut a = a.to_owned();
-    let mut m0 = m.to_owned();
-    let mut y = BigInt::zero();
-    let mut x = BigInt::one();
-    if m0.is_one() {
-        return BigInt::zero();
-    }
-    while a > BigInt::one() {
-        let t = m0.to_owned() / a.to_owned();
-        m0 = m0 - t.to_owned() * a.to_owned();
-        y = y - t.to_owned() * x.to_owned();
-        std::mem::swap(&mut a, &mut m0);
-        std::mem::swap(&mut x, &mut y);
+use std::collections::HashMap;
+
+// prime moduli are coprime and their product is at most the order, repeated
+// primes are reported rather than panicking
+fn chinese_remainder_theorem<E: Exponent>(mods: &[u128], rems: &[u128]) -> Result<E, RngError> {
+    let mods: Vec<BigUint> = mods.iter().map(|&m| BigUint::from(m)).collect();
+    let rems: Vec<BigUint> = rems.iter().map(|&r| BigUint::from(r)).collect();
+    let (result, _) = crt(&rems, &mods)?;
+    E::from_biguint(&result).ok_or(RngError::ModArith(ModArithError::Overflow))
+}
+
+// largest baby-step table worth attempting, about 2 ** 32 entries of
+// 8 + 8 * LIMBS bytes
+const MAX_STEP_SIZE: u128 = 1 << 32;
+
+// each prime as a u128 small enough for a baby-step table
+fn table_primes<E: Exponent>(primes: &[E]) -> Result<Vec<u128>, RngError> {
+    primes
+        .iter()
+        .map(|prime| {
+            prime
+                .to_biguint()
+                .to_u128()
+                .filter(|prime| step_size(*prime) <= MAX_STEP_SIZE)
+                .ok_or(RngError::TooLarge)
+        })
+        .collect()
+}
+
+fn step_size(order: u128) -> u128 {
+    let step_size = (order as f64).sqrt().ceil() as u128;
+    // f64 rounding for orders above 2 ** 53
+    if step_size
+        .checked_mul(step_size)
+        .is_some_and(|square| square >= order)
+    {
+        step_size
+    } else {
+        step_size + 1
     }
-    x
 }
 
-fn chinese_remainder_theorem(mods: Vec<u128>, rems: Vec<u128>) -> u128 {
-    let mut total = BigInt::zero();
-    let product: u128 = mods.iter().product();
-    let product_biguint = product.to_bigint().unwrap();
-    for i in 0..mods.len() {
-        let modulus = mods[i].to_bigint().unwrap();
-        let remainder = rems[i].to_bigint().unwrap();
-        let p = &product_biguint / &modulus;
-        let mut inv = modular_inverse(&p, &modulus);
-        if inv < BigInt::zero() {
-            inv += modulus.to_owned();
-        }
-        total += inv.to_owned() * remainder.to_owned() * p.to_owned();
+pub fn pohlig_hellman<E: Exponent, const LIMBS: usize>(
+    advance_poly: GF2Vec<LIMBS>,
+    backwards_poly: GF2Vec<LIMBS>,
+    jump_poly: GF2Vec<LIMBS>,
+    char_poly: GF2Vec<LIMBS>,
+    order: E,
+    primes: Vec<E>,
+) -> E {
+    try_pohlig_hellman(
+        advance_poly,
+        backwards_poly,
+        jump_poly,
+        char_poly,
+        order,
+        primes,
+    )
+    .unwrap_or_else(|error| panic!("{error}"))
+}
+
+// ``pohlig_hellman`` returning an error for a zero modulus or a jump poly
+// that is not a power of ``advance_poly`` instead of panicking
+pub fn try_pohlig_hellman<E: Exponent, const LIMBS: usize>(
+    advance_poly: GF2Vec<LIMBS>,
+    backwards_poly: GF2Vec<LIMBS>,
+    jump_poly: GF2Vec<LIMBS>,
+    char_poly: GF2Vec<LIMBS>,
+    order: E,
+    primes: Vec<E>,
+) -> Result<E, RngError> {
+    pohlig_hellman_with_progress(
+        advance_poly,
+        backwards_poly,
+        jump_poly,
+        char_poly,
+        order,
+        primes,
+        &Progress::new(),
+    )
+}
+
+// ``try_pohlig_hellman`` reporting each solved prime and stopping with
+// ``RngError::Cancelled`` once ``progress`` is cancelled
+pub fn pohlig_hellman_with_progress<E: Exponent, const LIMBS: usize>(
+    advance_poly: GF2Vec<LIMBS>,
+    backwards_poly: GF2Vec<LIMBS>,
+    jump_poly: GF2Vec<LIMBS>,
+    char_poly: GF2Vec<LIMBS>,
+    order: E,
+    primes: Vec<E>,
+    progress: &Progress,
+) -> Result<E, RngError> {
+    char_poly.checked_degree()?;
+    if jump_poly.modulo(char_poly).is_zero() {
+        return Err(RngError::LogNotFound);
     }
-    let mut val = total;
-    if val < BigInt::zero() {
-        val -= BigInt::one()
+    let table_primes = table_primes(&primes)?;
+    let mut remainders = vec![];
+    let mut mods = vec![];
+    for (index, (prime, table_prime)) in primes.iter().zip(&table_primes).enumerate() {
+        progress.check()?;
+        let exp = order.clone() / prime.clone();
+        let table = BabyStepTable::new_with_progress(
+            advance_poly.modpow(exp.clone(), char_poly),
+            backwards_poly.modpow(exp.clone(), char_poly),
+            char_poly,
+            *table_prime,
+            progress,
+        )?;
+        let h_i = jump_poly.modpow(exp, char_poly);
+        remainders.push(table.solve_with_progress(h_i, char_poly, progress)?);
+        progress.report(ProgressEvent::PrimeSolved {
+            prime: *table_prime,
+            index,
+            count: primes.len(),
+        });
+        mods.push(*table_prime);
+        let jmp: E = chinese_remainder_theorem(&mods, &remainders)?;
+        let test_jump = compute_jump_poly(jmp.clone(), char_poly);
+        if test_jump == jump_poly {
+            return Ok(jmp);
+        }
     }
-    (val % product_biguint).try_into().unwrap()
+
+    Err(RngError::LogNotFound)
 }
 
-fn baby_step_giant_step(
-    _gamma_poly: GF2Vec128,
-    _h_poly: GF2Vec128,
-    backwards_poly: GF2Vec128,
-    char_poly: GF2Vec128,
-    order: u128,
-) -> u128 {
-    let mut gamma_poly = GF2Vec128 {
-        state_high: 0,
-        state_low: 1,
-    };
-    let mut h_poly = _h_poly.clone();
-    let step_size = (order as f64).sqrt().ceil() as u128;
-    let backward_jump_poly = backwards_poly.modpow(step_size, char_poly);
-    let result: Vec<u128> = (0..step_size).into_par_iter().collect();
-    if step_size > current_num_threads().try_into().unwrap() {
-        let lookup_vec: Vec<_> = result
-            .par_chunks((step_size as usize) / current_num_threads())
-            .map(|chunk| {
-                let base = chunk[0];
-                let mut base_poly = _gamma_poly.modpow(base, char_poly);
-                chunk
-                    .iter()
-                    .map(|_| {
-                        let state_low = base_poly.state_low;
-                        base_poly = base_poly.mul(_gamma_poly).modulo(char_poly);
-                        state_low
+// giant steps of z ** -step_size looked up among the baby steps z ** j,
+// keyed on the whole reduced polynomial
+pub struct BabyStepTable<const LIMBS: usize> {
+    step_size: u128,
+    backward_jump_poly: GF2Vec<LIMBS>,
+    lookup_table: HashMap<GF2Vec<LIMBS>, u64>,
+}
+
+impl<const LIMBS: usize> BabyStepTable<LIMBS> {
+    pub fn new(
+        gamma_poly: GF2Vec<LIMBS>,
+        backwards_poly: GF2Vec<LIMBS>,
+        char_poly: GF2Vec<LIMBS>,
+        order: u128,
+    ) -> Result<BabyStepTable<LIMBS>, RngError> {
+        BabyStepTable::new_with_progress(
+            gamma_poly,
+            backwards_poly,
+            char_poly,
+            order,
+            &Progress::new(),
+        )
+    }
+
+    pub fn new_with_progress(
+        gamma_poly: GF2Vec<LIMBS>,
+        backwards_poly: GF2Vec<LIMBS>,
+        char_poly: GF2Vec<LIMBS>,
+        order: u128,
+        progress: &Progress,
+    ) -> Result<BabyStepTable<LIMBS>, RngError> {
+        char_poly.checked_degree()?;
+        let step_size = step_size(order);
+        if step_size > MAX_STEP_SIZE {
+            return Err(RngError::TooLarge);
+        }
+        let backward_jump_poly = backwards_poly.modpow(step_size, char_poly);
+        let chunk_size = (step_size / current_num_threads() as u128).max(1);
+        let chunk_count = step_size.div_ceil(chunk_size);
+        let lookup_table = (0..chunk_count)
+            .into_par_iter()
+            .flat_map_iter(|chunk| {
+                let base = chunk * chunk_size;
+                let mut base_poly = gamma_poly.modpow(base, char_poly);
+                // cancelled chunks stop early and the table is discarded
+                (base..(base + chunk_size).min(step_size))
+                    .take_while(|j| !j.is_multiple_of(CHECK_INTERVAL) || !progress.is_cancelled())
+                    .map(move |j| {
+                        let key = base_poly;
+                        base_poly = (base_poly * gamma_poly).modulo(char_poly);
+                        (key, j as u64)
                     })
-                    .collect::<Vec<u128>>()
             })
             .collect();
-        let lookup_vec: Vec<_> = lookup_vec.into_iter().flat_map(|v| v.into_iter()).collect();
-        let mut lookup_table = HashSet::<u128>::new();
-        for item in &lookup_vec {
-            lookup_table.insert(*item);
-        }
-        return result
-            .par_chunks((step_size as usize) / current_num_threads())
+        progress.check()?;
+        Ok(BabyStepTable {
+            step_size,
+            backward_jump_poly,
+            lookup_table,
+        })
+    }
+
+    // ``RngError::LogNotFound`` when ``h_poly`` is not a power of gamma
+    pub fn solve(&self, h_poly: GF2Vec<LIMBS>, char_poly: GF2Vec<LIMBS>) -> Result<u128, RngError> {
+        self.solve_with_progress(h_poly, char_poly, &Progress::new())
+    }
+
+    pub fn solve_with_progress(
+        &self,
+        h_poly: GF2Vec<LIMBS>,
+        char_poly: GF2Vec<LIMBS>,
+        progress: &Progress,
+    ) -> Result<u128, RngError> {
+        let step_size = self.step_size;
+        let chunk_size = (step_size / current_num_threads() as u128).max(1);
+        let chunk_count = step_size.div_ceil(chunk_size);
+        (0..chunk_count)
+            .into_par_iter()
             .find_map_any(|chunk| {
-                let base = chunk[0];
-                let mut base_poly = h_poly
-                    .mul(backward_jump_poly.modpow(base, char_poly))
-                    .modulo(char_poly);
-                chunk.iter().find_map(|i| {
-                    if lookup_table.contains(&base_poly.state_low) {
-                        let j = lookup_vec
-                            .iter()
-                            .position(|&r| r == base_poly.state_low)
-                            .unwrap() as u128;
-                        return Some(i * step_size + j);
+                let base = chunk * chunk_size;
+                let mut base_poly =
+                    (h_poly * self.backward_jump_poly.modpow(base, char_poly)).modulo(char_poly);
+                (base..(base + chunk_size).min(step_size)).find_map(|i| {
+                    if i.is_multiple_of(CHECK_INTERVAL) && progress.is_cancelled() {
+                        return Some(Err(RngError::Cancelled));
                     }
-                    base_poly = base_poly.mul(backward_jump_poly).modulo(char_poly);
+                    if let Some(j) = self.lookup_table.get(&base_poly) {
+                        return Some(Ok(i * step_size + *j as u128));
+                    }
+                    base_poly = (base_poly * self.backward_jump_poly).modulo(char_poly);
                     None
                 })
             })
-            .unwrap();
-    } else {
-        let mut lookup_table: Vec<u128> = vec![];
-        for _ in 0..step_size {
-            lookup_table.push(gamma_poly.state_low);
-            gamma_poly = gamma_poly.mul(_gamma_poly).modulo(char_poly);
+            .unwrap_or(Err(RngError::LogNotFound))
+    }
+}
+
+// precomputes the baby-step tables of every prime so that many jump polys
+// can be solved against the same group
+pub struct PohligHellmanSolver<E: Exponent, const LIMBS: usize> {
+    char_poly: GF2Vec<LIMBS>,
+    order: E,
+    primes: Vec<E>,
+    table_primes: Vec<u128>,
+    tables: Vec<BabyStepTable<LIMBS>>,
+}
+
+impl<E: Exponent, const LIMBS: usize> PohligHellmanSolver<E, LIMBS> {
+    pub fn new(
+        advance_poly: GF2Vec<LIMBS>,
+        backwards_poly: GF2Vec<LIMBS>,
+        char_poly: GF2Vec<LIMBS>,
+        order: E,
+        primes: Vec<E>,
+    ) -> PohligHellmanSolver<E, LIMBS> {
+        PohligHellmanSolver::new_with_progress(
+            advance_poly,
+            backwards_poly,
+            char_poly,
+            order,
+            primes,
+            &Progress::new(),
+        )
+        .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn new_with_progress(
+        advance_poly: GF2Vec<LIMBS>,
+        backwards_poly: GF2Vec<LIMBS>,
+        char_poly: GF2Vec<LIMBS>,
+        order: E,
+        primes: Vec<E>,
+        progress: &Progress,
+    ) -> Result<PohligHellmanSolver<E, LIMBS>, RngError> {
+        let table_primes = table_primes(&primes)?;
+        let mut tables = vec![];
+        for (index, (prime, table_prime)) in primes.iter().zip(&table_primes).enumerate() {
+            let exp = order.clone() / prime.clone();
+            tables.push(BabyStepTable::new_with_progress(
+                advance_poly.modpow(exp.clone(), char_poly),
+                backwards_poly.modpow(exp, char_poly),
+                char_poly,
+                *table_prime,
+                progress,
+            )?);
+            progress.report(ProgressEvent::TableBuilt {
+                prime: *table_prime,
+                index,
+                count: primes.len(),
+            });
         }
-        for i in 0..step_size {
-            if lookup_table.contains(&h_poly.state_low) {
-                let j = lookup_table
-                    .iter()
-                    .position(|&r| r == h_poly.state_low)
-                    .unwrap() as u128;
-                return i * step_size + j;
+        Ok(PohligHellmanSolver {
+            char_poly,
+            order,
+            primes,
+            table_primes,
+            tables,
+        })
+    }
+
+    pub fn solve(&self, jump_poly: GF2Vec<LIMBS>) -> E {
+        self.try_solve(jump_poly)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_solve(&self, jump_poly: GF2Vec<LIMBS>) -> Result<E, RngError> {
+        self.solve_with_progress(jump_poly, &Progress::new())
+    }
+
+    pub fn solve_with_progress(
+        &self,
+        jump_poly: GF2Vec<LIMBS>,
+        progress: &Progress,
+    ) -> Result<E, RngError> {
+        if jump_poly.modulo(self.char_poly).is_zero() {
+            return Err(RngError::LogNotFound);
+        }
+        let mut remainders = vec![];
+        let mut mods = vec![];
+        let primes = self.primes.iter().zip(&self.table_primes).zip(&self.tables);
+        for (index, ((prime, table_prime), table)) in primes.enumerate() {
+            let h_i = jump_poly.modpow(self.order.clone() / prime.clone(), self.char_poly);
+            remainders.push(table.solve_with_progress(h_i, self.char_poly, progress)?);
+            progress.report(ProgressEvent::PrimeSolved {
+                prime: *table_prime,
+                index,
+                count: self.primes.len(),
+            });
+            mods.push(*table_prime);
+            let jmp: E = chinese_remainder_theorem(&mods, &remainders)?;
+            let test_jump = compute_jump_poly(jmp.clone(), self.char_poly);
+            if test_jump == jump_poly {
+                return Ok(jmp);
             }
-            h_poly = h_poly.mul(backward_jump_poly).modulo(char_poly);
         }
-    }
 
-    panic!("Remainder Not Found");
+        Err(RngError::LogNotFound)
+    }
 }
 
-pub fn pohlig_hellman(
-    advance_poly: GF2Vec128,
-    backwards_poly: GF2Vec128,
-    jump_poly: GF2Vec128,
-    char_poly: GF2Vec128,
-    order: u128,
-    primes: Vec<u128>,
-) -> u128 {
-    let mut remainders = vec![];
-    let mut mods = vec![];
-    for prime in &primes {
-        let exp = order / prime;
-        let g_i = advance_poly.modpow(exp, char_poly);
-        let h_i = jump_poly.modpow(exp, char_poly);
-        let b_i = backwards_poly.modpow(exp, char_poly);
-        remainders.push(baby_step_giant_step(g_i, h_i, b_i, char_poly, *prime));
-        mods.push(prime.to_owned());
-        let jmp = chinese_remainder_theorem(mods.to_owned(), remainders.to_owned());
-        let test_jump = compute_jump_poly(jmp, char_poly);
-        if test_jump.state_low == jump_poly.state_low {
-            return jmp;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gf2vec::{base_z_modpow, GF2Vec64};
+
+    // x^4 + x + 1 is primitive, z generating a group of order 15
+    const CHAR_POLY: GF2Vec64 = GF2Vec64::from_u128(0b10011);
+
+    #[test]
+    fn baby_step_table_solves_every_power() {
+        let z = GF2Vec64::Z;
+        let table =
+            BabyStepTable::new(z, z.inverse_mod(CHAR_POLY).unwrap(), CHAR_POLY, 15).unwrap();
+        for k in 0..15u128 {
+            assert_eq!(table.solve(base_z_modpow(k, CHAR_POLY), CHAR_POLY), Ok(k));
         }
+        assert_eq!(
+            table.solve(GF2Vec64::ZERO, CHAR_POLY),
+            Err(RngError::LogNotFound)
+        );
     }
 
-    chinese_remainder_theorem(primes, remainders)
+    #[test]
+    fn pohlig_hellman_recombines_prime_logs() {
+        let z = GF2Vec64::Z;
+        let backwards = z.inverse_mod(CHAR_POLY).unwrap();
+        for k in 1..15u128 {
+            let jump_poly = base_z_modpow(k, CHAR_POLY);
+            assert_eq!(
+                try_pohlig_hellman(z, backwards, jump_poly, CHAR_POLY, 15u128, vec![3, 5]),
+                Ok(k)
+            );
+        }
+        assert_eq!(
+            try_pohlig_hellman(
+                z,
+                backwards,
+                GF2Vec64::ONE,
+                GF2Vec64::ZERO,
+                15u128,
+                vec![3, 5]
+            ),
+            Err(RngError::ZeroPolynomial)
+        );
+        // primes whose baby-step tables could never be built
+        let huge = vec![5704689200685129054721u128];
+        assert_eq!(
+            try_pohlig_hellman(z, backwards, z, CHAR_POLY, 15u128, huge),
+            Err(RngError::TooLarge)
+        );
+    }
 }
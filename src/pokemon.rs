@@ -0,0 +1,138 @@
+use crate::export::serialize_hex;
+use serde::Serialize;
+
+// shared representation of generated pokemon across the game modules
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum ShinyType {
+    None,
+    Star,
+    Square,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum Gender {
+    Male,
+    Female,
+    Genderless,
+}
+
+// ability slot rolled out of the first two or all three
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum AbilityType {
+    Fixed(u8),
+    NoHidden,
+    Any,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Pokemon {
+    #[serde(serialize_with = "serialize_hex")]
+    pub ec: u32,
+    #[serde(serialize_with = "serialize_hex")]
+    pub pid: u32,
+    pub shiny: ShinyType,
+    pub ivs: [u8; 6],
+    pub ability: u8,
+    pub gender: Gender,
+    pub nature: u8,
+}
+
+impl AsRef<Pokemon> for Pokemon {
+    fn as_ref(&self) -> &Pokemon {
+        self
+    }
+}
+
+// xor of the pid halves and the trainer's tid/sid halves
+pub fn shiny_xor(pid: u32, tidsid: u32) -> u32 {
+    let xor = pid ^ tidsid;
+    (xor >> 16) ^ (xor & 0xFFFF)
+}
+
+pub fn shiny_type(pid: u32, tidsid: u32) -> ShinyType {
+    match shiny_xor(pid, tidsid) {
+        0 => ShinyType::Square,
+        1..=15 => ShinyType::Star,
+        _ => ShinyType::None,
+    }
+}
+
+// the pid the game stores for the real trainer: shiny for the fake trainer is
+// forced shiny of the same type, otherwise shininess is prevented
+pub fn apply_trainer_shininess(pid: u32, fake_tidsid: u32, tidsid: u32) -> u32 {
+    let fake_shiny = shiny_type(pid, fake_tidsid);
+    if fake_shiny != ShinyType::None {
+        if shiny_type(pid, tidsid) == ShinyType::None {
+            let low = pid & 0xFFFF;
+            let xor = if fake_shiny == ShinyType::Square {
+                0
+            } else {
+                1
+            };
+            return (((tidsid >> 16) ^ tidsid ^ low ^ xor) << 16) | low;
+        }
+    } else if shiny_type(pid, tidsid) != ShinyType::None {
+        return pid ^ 0x10000000;
+    }
+    pid
+}
+
+// bits of a stored pid that match the rolled pid, the high half is replaced
+// when forced shiny and bit 28 may be flipped when shininess was prevented
+pub fn rolled_pid_mask(pokemon: &Pokemon) -> u64 {
+    if pokemon.shiny == ShinyType::None {
+        0xEFFFFFFF
+    } else {
+        0xFFFF
+    }
+}
+
+// guaranteed 31s at distinct rerolled stats, the rest random
+pub fn roll_ivs(flawless_ivs: u8, mut rand: impl FnMut(u64) -> u64) -> [u8; 6] {
+    let mut ivs = [None; 6];
+    for _ in 0..flawless_ivs {
+        loop {
+            let stat = rand(6) as usize;
+            if ivs[stat].is_none() {
+                ivs[stat] = Some(31);
+                break;
+            }
+        }
+    }
+    ivs.map(|iv| iv.unwrap_or_else(|| rand(32) as u8))
+}
+
+// gens 3 to 5 only count xors below 8 as shiny
+pub fn legacy_shiny_type(pid: u32, tidsid: u32) -> ShinyType {
+    match shiny_xor(pid, tidsid) {
+        0 => ShinyType::Square,
+        1..=7 => ShinyType::Star,
+        _ => ShinyType::None,
+    }
+}
+
+// gens 3 to 5 derive everything but the ivs from the pid, which doubles as
+// the encryption constant
+pub fn legacy_pokemon(pid: u32, ivs: [u8; 6], tidsid: u32, gender_ratio: u8) -> Pokemon {
+    Pokemon {
+        ec: pid,
+        pid,
+        shiny: legacy_shiny_type(pid, tidsid),
+        ivs,
+        ability: (pid & 1) as u8,
+        gender: match gender_ratio {
+            255 => Gender::Genderless,
+            254 => Gender::Female,
+            0 => Gender::Male,
+            ratio => {
+                if ((pid & 0xFF) as u8) < ratio {
+                    Gender::Female
+                } else {
+                    Gender::Male
+                }
+            }
+        },
+        nature: (pid % 25) as u8,
+    }
+}
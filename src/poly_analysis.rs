@@ -0,0 +1,198 @@
+use crate::{
+    berkowitz::CharPoly,
+    error::RngError,
+    gf2int::GF2Int,
+    gf2vec::{base_z_modpow, Exponent, GF2Vec},
+    rng::RNG,
+};
+use num_bigint::BigUint;
+use num_traits::One;
+
+// ``LIMBS`` needs twice the state size so products fit before reduction
+pub fn charpoly<R: RNG<MatrixInt = GF2Int>, const LIMBS: usize>() -> GF2Vec<LIMBS> {
+    let mut char_poly = R::matrix().compute_charpoly_coeffs();
+    char_poly.reverse();
+    GF2Vec::new(char_poly)
+}
+
+// 2 ** degree - 1, the order of the multiplicative group of GF(2 ** degree)
+pub fn group_order(degree: u32) -> BigUint {
+    (BigUint::one() << degree) - 1u32
+}
+
+fn distinct_prime_factors(n: u32) -> Vec<u32> {
+    let mut n = n;
+    let mut primes = vec![];
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            primes.push(p);
+            while n.is_multiple_of(p) {
+                n /= p;
+            }
+        }
+        p += 1;
+    }
+    if n > 1 {
+        primes.push(n);
+    }
+    primes
+}
+
+// z ** (2 ** k) mod poly via repeated squaring
+fn z_pow_pow2<const LIMBS: usize>(k: u32, poly: GF2Vec<LIMBS>) -> GF2Vec<LIMBS> {
+    let mut result = GF2Vec::Z.modulo(poly);
+    for _ in 0..k {
+        result = result.square().modulo(poly);
+    }
+    result
+}
+
+// rabin's test: z ** (2 ** n) ≡ z and gcd(z ** (2 ** (n / q)) - z, poly) = 1
+// for every prime q dividing n
+pub fn is_irreducible<const LIMBS: usize>(poly: GF2Vec<LIMBS>) -> bool {
+    if poly.is_zero() {
+        return false;
+    }
+    let degree = poly.degree();
+    if degree == 0 {
+        return false;
+    }
+    if (z_pow_pow2(degree, poly) + GF2Vec::Z)
+        .modulo(poly)
+        .is_zero()
+    {
+        distinct_prime_factors(degree).iter().all(|q| {
+            let residue = (z_pow_pow2(degree / q, poly) + GF2Vec::Z).modulo(poly);
+            !residue.is_zero() && residue.gcd(poly).is_one()
+        })
+    } else {
+        false
+    }
+}
+
+// irreducible and z generates the full group of order 2 ** n - 1,
+// ``primes`` being the prime factors of 2 ** n - 1
+pub fn is_primitive<E: Exponent, const LIMBS: usize>(poly: GF2Vec<LIMBS>, primes: &[E]) -> bool {
+    if !is_irreducible(poly) {
+        return false;
+    }
+    let order = group_order(poly.degree());
+    primes
+        .iter()
+        .all(|prime| !base_z_modpow(&order / prime.to_biguint(), poly).is_one())
+}
+
+// order of z mod poly given a multiple ``group_order`` of it and the prime
+// factors of ``group_order``
+pub fn multiplicative_order<E: Exponent, const LIMBS: usize>(
+    poly: GF2Vec<LIMBS>,
+    group_order: E,
+    primes: &[E],
+) -> Result<E, RngError> {
+    poly.checked_degree()?;
+    if !base_z_modpow(group_order.clone(), poly).is_one() {
+        return Err(RngError::NotGroupOrder);
+    }
+    let mut order = group_order;
+    for prime in primes {
+        while order.is_multiple_of(prime)
+            && base_z_modpow(order.clone() / prime.clone(), poly).is_one()
+        {
+            order = order / prime.clone();
+        }
+    }
+    Ok(order)
+}
+
+// period of a generator whose characteristic polynomial is primitive, None
+// if it is not or if the period does not fit ``E``
+pub fn primitive_period<R: RNG<MatrixInt = GF2Int>, E: Exponent, const LIMBS: usize>(
+    primes: &[E],
+) -> Option<E> {
+    let char_poly = charpoly::<R, LIMBS>();
+    if is_primitive(char_poly, primes) {
+        E::from_biguint(&group_order(char_poly.degree()))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        berlekamp_massey::{minimal_polynomial, output_sequence},
+        gf2vec::{GF2Vec256, GF2Vec64},
+        xoroshiro::Xoroshiro64Star,
+        xoshiro::Xoshiro256Plus,
+    };
+
+    // x^4 + x + 1 is primitive, x^4 + x^3 + x^2 + x + 1 irreducible with z of
+    // order 5, x^4 + x^2 + 1 = (x^2 + x + 1) ** 2
+    const PRIMITIVE: GF2Vec64 = GF2Vec64::from_u128(0b10011);
+    const IRREDUCIBLE: GF2Vec64 = GF2Vec64::from_u128(0b11111);
+    const REDUCIBLE: GF2Vec64 = GF2Vec64::from_u128(0b10101);
+
+    #[test]
+    fn classifies_small_polynomials() {
+        assert!(is_irreducible(PRIMITIVE) && is_primitive(PRIMITIVE, &[3u128, 5]));
+        assert!(is_irreducible(IRREDUCIBLE) && !is_primitive(IRREDUCIBLE, &[3u128, 5]));
+        assert!(!is_irreducible(REDUCIBLE));
+        // x^127 + x + 1, 2 ** 127 - 1 being prime
+        let trinomial = (GF2Vec256::ONE << 127) + GF2Vec256::from(0b11u64);
+        assert!(is_primitive(trinomial, &[u128::MAX >> 1]));
+    }
+
+    #[test]
+    fn multiplicative_order_checks_the_group_order() {
+        assert_eq!(multiplicative_order(PRIMITIVE, 15u128, &[3, 5]), Ok(15));
+        assert_eq!(multiplicative_order(IRREDUCIBLE, 15u128, &[3, 5]), Ok(5));
+        assert_eq!(
+            multiplicative_order(PRIMITIVE, 7u128, &[7]),
+            Err(RngError::NotGroupOrder)
+        );
+        assert_eq!(
+            multiplicative_order(GF2Vec64::ZERO, 15u128, &[3, 5]),
+            Err(RngError::ZeroPolynomial)
+        );
+    }
+
+    #[test]
+    fn handles_degrees_above_128() {
+        let mut rng = Xoshiro256Plus::new([1, 2, 3, 4]);
+        let sequence = output_sequence(&mut rng, 512, |rng| GF2Int::new((rng.seed[0] & 1) as u8));
+        let char_poly: GF2Vec256 = minimal_polynomial(&sequence);
+        assert_eq!(char_poly.degree(), 256);
+        let primes: Vec<BigUint> = [
+            3u128,
+            5,
+            17,
+            257,
+            641,
+            65537,
+            274177,
+            6700417,
+            67280421310721,
+            59649589127497217,
+            5704689200685129054721,
+        ]
+        .iter()
+        .map(|prime| BigUint::from(*prime))
+        .collect();
+        assert!(is_primitive(char_poly, &primes));
+        assert_eq!(
+            multiplicative_order(char_poly, group_order(256), &primes),
+            Ok(group_order(256))
+        );
+    }
+
+    #[test]
+    fn xoroshiro64_has_full_period() {
+        let primes = [3u128, 5, 17, 257, 641, 65537, 6700417];
+        assert_eq!(
+            primitive_period::<Xoroshiro64Star, u128, 2>(&primes),
+            Some(u64::MAX as u128)
+        );
+    }
+}
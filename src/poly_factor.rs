@@ -0,0 +1,329 @@
+use crate::{
+    error::RngError,
+    gf2int::GF2Int,
+    gf2vec::{base_z_modpow, Exponent, GF2Vec},
+    jump::{jump_application_mat, jump_poly_from_state},
+    mat_builder::MatInverse,
+    modarith::{crt_general, ModArithError},
+    pohlig_hellman::BabyStepTable,
+    poly_analysis::{charpoly, multiplicative_order},
+    progress::{ChunkCounter, Progress},
+    rng::RNG,
+};
+use num_traits::Zero;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+pub use crate::modarith::prime_factors;
+
+// factoring works for any width, discrete logs need every irreducible factor
+// of degree at most 128 so that the group orders fit a u128
+
+fn derivative<const LIMBS: usize>(poly: GF2Vec<LIMBS>) -> GF2Vec<LIMBS> {
+    let even_bits = GF2Vec {
+        limbs: [0x5555555555555555; LIMBS],
+    };
+    // d/dz z ** i = i * z ** (i - 1), which vanishes for even i
+    (poly >> 1) & even_bits
+}
+
+// square root of a polynomial with only even powers
+fn sqrt<const LIMBS: usize>(poly: GF2Vec<LIMBS>) -> GF2Vec<LIMBS> {
+    let mut result = GF2Vec::ZERO;
+    for i in 0..GF2Vec::<LIMBS>::BITS / 2 {
+        if poly.bit(2 * i) {
+            result ^= GF2Vec::ONE << i;
+        }
+    }
+    result
+}
+
+// (factor, multiplicity) pairs whose factors are square-free and pairwise
+// coprime
+pub fn square_free_factorization<const LIMBS: usize>(
+    poly: GF2Vec<LIMBS>,
+) -> Vec<(GF2Vec<LIMBS>, u32)> {
+    let mut factors = vec![];
+    let mut c = poly.gcd(derivative(poly));
+    let mut w = poly.div_rem(c).0;
+    let mut multiplicity = 1;
+    while !w.is_one() {
+        let y = w.gcd(c);
+        let factor = w.div_rem(y).0;
+        if !factor.is_one() {
+            factors.push((factor, multiplicity));
+        }
+        w = y;
+        c = c.div_rem(y).0;
+        multiplicity += 1;
+    }
+    if !c.is_one() {
+        for (factor, multiplicity) in square_free_factorization(sqrt(c)) {
+            factors.push((factor, multiplicity * 2));
+        }
+    }
+    factors
+}
+
+// (product, degree) pairs where product is the product of every irreducible
+// factor of ``poly`` of that degree, ``poly`` must be square-free
+pub fn distinct_degree_factorization<const LIMBS: usize>(
+    poly: GF2Vec<LIMBS>,
+) -> Vec<(GF2Vec<LIMBS>, u32)> {
+    let mut factors = vec![];
+    let mut remaining = poly;
+    let mut z_pow = GF2Vec::Z;
+    let mut degree = 1;
+    while !remaining.is_one() && remaining.degree() >= 2 * degree {
+        z_pow = z_pow.square().modulo(remaining);
+        let factor = remaining.gcd(z_pow + GF2Vec::Z);
+        if !factor.is_one() {
+            factors.push((factor, degree));
+            remaining = remaining.div_rem(factor).0;
+            z_pow = z_pow.modulo(remaining);
+        }
+        degree += 1;
+    }
+    if !remaining.is_one() {
+        factors.push((remaining, remaining.degree()));
+    }
+    factors
+}
+
+// cantor-zassenhaus splitting of a product of irreducibles of equal
+// ``degree``, using the trace map since the field has characteristic 2
+pub fn equal_degree_factorization<const LIMBS: usize>(
+    poly: GF2Vec<LIMBS>,
+    degree: u32,
+) -> Vec<GF2Vec<LIMBS>> {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut factors = vec![];
+    let mut pending = vec![poly];
+    while let Some(poly) = pending.pop() {
+        if poly.degree() == degree {
+            factors.push(poly);
+            continue;
+        }
+        loop {
+            let random = GF2Vec {
+                limbs: std::array::from_fn(|_| rng.gen()),
+            }
+            .modulo(poly);
+            let mut trace = random;
+            let mut term = random;
+            for _ in 1..degree {
+                term = term.square().modulo(poly);
+                trace ^= term;
+            }
+            let factor = poly.gcd(trace);
+            if !factor.is_one() && factor.degree() < poly.degree() {
+                pending.push(factor);
+                pending.push(poly.div_rem(factor).0);
+                break;
+            }
+        }
+    }
+    factors
+}
+
+// irreducible factors with their multiplicities
+pub fn factor<const LIMBS: usize>(poly: GF2Vec<LIMBS>) -> Vec<(GF2Vec<LIMBS>, u32)> {
+    let mut factors = vec![];
+    for (square_free, multiplicity) in square_free_factorization(poly) {
+        for (product, degree) in distinct_degree_factorization(square_free) {
+            for factor in equal_degree_factorization(product, degree) {
+                factors.push((factor, multiplicity));
+            }
+        }
+    }
+    factors.sort_by_key(|(factor, _)| *factor);
+    factors
+}
+
+// pohlig-hellman for k with z ** k ≡ target (mod poly) where ``order`` is the
+// order of z, handling prime powers digit by digit
+fn log_in_subgroup<const LIMBS: usize>(
+    target: GF2Vec<LIMBS>,
+    poly: GF2Vec<LIMBS>,
+    order: u128,
+    progress: &Progress,
+) -> Result<u128, RngError> {
+    if !target.modpow(order, poly).is_one() {
+        return Err(RngError::LogNotFound);
+    }
+    let mut congruences = vec![];
+    for (prime, exponent) in prime_factors(order) {
+        let gamma = base_z_modpow(order / prime, poly);
+        let gamma_inverse = gamma.try_inverse_mod(poly)?;
+        let table = BabyStepTable::new_with_progress(gamma, gamma_inverse, poly, prime, progress)?;
+        let mut prime_power = 1;
+        let mut x = 0;
+        for _ in 0..exponent {
+            prime_power *= prime;
+            // target * z ** -x
+            let h =
+                (target * base_z_modpow(order - x, poly) % poly).modpow(order / prime_power, poly);
+            x += table.solve_with_progress(h, poly, progress)? * (prime_power / prime);
+        }
+        congruences.push((x, prime_power));
+    }
+    combine(&congruences)
+}
+
+// inconsistent congruences mean the target is no power of z
+fn combine(congruences: &[(u128, u128)]) -> Result<u128, RngError> {
+    match crt_general(congruences) {
+        Ok((remainder, _)) => Ok(remainder),
+        Err(ModArithError::Inconsistent) => Err(RngError::LogNotFound),
+        Err(error) => Err(error.into()),
+    }
+}
+
+// smallest k with z ** k ≡ jump_poly (mod char_poly), solved separately
+// modulo each prime power factor of ``char_poly`` and recombined with the crt
+pub fn discrete_log<const LIMBS: usize>(
+    jump_poly: GF2Vec<LIMBS>,
+    char_poly: GF2Vec<LIMBS>,
+) -> Result<u128, RngError> {
+    discrete_log_with_progress(jump_poly, char_poly, &Progress::new())
+}
+
+// each solved factor is a chunk
+pub fn discrete_log_with_progress<const LIMBS: usize>(
+    jump_poly: GF2Vec<LIMBS>,
+    char_poly: GF2Vec<LIMBS>,
+    progress: &Progress,
+) -> Result<u128, RngError> {
+    char_poly.checked_degree()?;
+    let factors = factor(char_poly);
+    let counter = ChunkCounter::new(progress, factors.len() as u128);
+    let mut congruences = vec![];
+    for (factor, multiplicity) in factors {
+        progress.check()?;
+        if factor == GF2Vec::Z {
+            // z is not invertible, the generator loses state
+            return Err(RngError::NotInvertible);
+        }
+        if factor.degree() > 128 {
+            return Err(RngError::TooLarge);
+        }
+        let factor_order = u128::MAX >> (128 - factor.degree());
+        let order = multiplicative_order(
+            factor,
+            factor_order,
+            &prime_factors(factor_order)
+                .iter()
+                .map(|(prime, _)| *prime)
+                .collect::<Vec<_>>(),
+        )?;
+        let remainder = log_in_subgroup(jump_poly.modulo(factor), factor, order, progress)?;
+
+        // z ** order ≡ 1 + factor * h, so the order modulo factor ** e is
+        // order * 2 ** s for the smallest such s
+        let mut prime_power = GF2Vec::ONE;
+        for _ in 0..multiplicity {
+            prime_power = prime_power * factor;
+        }
+        let mut power_order = order;
+        while !base_z_modpow(power_order, prime_power).is_one() {
+            power_order = power_order.checked_mul(2).ok_or(RngError::TooLarge)?;
+        }
+        let target = jump_poly.modulo(prime_power);
+        let remainder = (0..power_order / order)
+            .map(|m| remainder + m * order)
+            .find(|k| base_z_modpow(*k, prime_power) == target)
+            .ok_or(RngError::LogNotFound)?;
+        congruences.push((remainder, power_order));
+        counter.finish();
+    }
+    combine(&congruences)
+}
+
+// distance between two states of a generator whose characteristic
+// polynomial need not be irreducible, ``LIMBS`` being twice the state size;
+// the origin's orbit has to span the state space for the jump polynomial to
+// be unique
+pub fn distance<R, const LIMBS: usize>(mut origin: R, mut target: R) -> Result<u128, RngError>
+where
+    R: RNG<MatrixInt = GF2Int> + Copy,
+    R::AdvanceInt: Exponent,
+{
+    if origin.state().is_zero() || target.state().is_zero() {
+        return Err(RngError::ZeroState);
+    }
+    let char_poly = charpoly::<R, LIMBS>();
+    let size = char_poly.checked_degree()? as usize;
+    let jump_application_mat_inv = jump_application_mat(origin, size).try_inverse()?;
+    let jump_poly = jump_poly_from_state(&jump_application_mat_inv, target);
+    discrete_log(jump_poly, char_poly)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gf2vec::GF2Vec64, xoroshiro::Xoroshiro64Star};
+
+    const X_PLUS_1: GF2Vec64 = GF2Vec64::from_u128(0b11);
+    const X2_X_1: GF2Vec64 = GF2Vec64::from_u128(0b111);
+    const X3_X_1: GF2Vec64 = GF2Vec64::from_u128(0b1011);
+    const X4_X_1: GF2Vec64 = GF2Vec64::from_u128(0b10011);
+
+    fn power(poly: GF2Vec64, exponent: u32) -> GF2Vec64 {
+        (0..exponent).fold(GF2Vec64::ONE, |result, _| result * poly)
+    }
+
+    #[test]
+    fn factors_with_multiplicities() {
+        let poly = power(X_PLUS_1, 3) * X2_X_1 * power(X3_X_1, 2) * power(X4_X_1, 5);
+        assert_eq!(poly.degree(), 3 + 2 + 6 + 20);
+        assert_eq!(
+            factor(poly),
+            vec![(X_PLUS_1, 3), (X2_X_1, 1), (X3_X_1, 2), (X4_X_1, 5)]
+        );
+        // x^5 + x + 1 = (x^2 + x + 1)(x^3 + x^2 + 1)
+        assert_eq!(
+            factor(GF2Vec64::from_u128(0b100011)),
+            vec![(X2_X_1, 1), (GF2Vec64::from_u128(0b1101), 1)]
+        );
+        assert_eq!(factor(X4_X_1), vec![(X4_X_1, 1)]);
+    }
+
+    #[test]
+    fn discrete_log_over_composite_moduli() {
+        // orders 3, 7 and 15 lcm to 105, doubled by the square of x^3 + x + 1
+        let char_poly = X2_X_1 * power(X3_X_1, 2) * X4_X_1;
+        for k in [0u128, 1, 2, 29, 104, 150, 209] {
+            let jump_poly = base_z_modpow(k, char_poly);
+            assert_eq!(discrete_log(jump_poly, char_poly), Ok(k % 210));
+        }
+        // 0 is no power of z
+        let char_poly = X2_X_1 * X4_X_1;
+        assert_eq!(
+            discrete_log(GF2Vec64::ZERO, char_poly),
+            Err(RngError::LogNotFound)
+        );
+        // z is not invertible modulo a multiple of x
+        assert_eq!(
+            discrete_log(GF2Vec64::ONE, X4_X_1 * GF2Vec64::Z),
+            Err(RngError::NotInvertible)
+        );
+
+        let progress = Progress::new();
+        progress.cancel();
+        assert_eq!(
+            discrete_log_with_progress(base_z_modpow(5u128, char_poly), char_poly, &progress),
+            Err(RngError::Cancelled)
+        );
+    }
+
+    #[test]
+    fn distance_between_states() {
+        let origin = Xoroshiro64Star::new(0xdead, 0xbeef);
+        let mut target = origin;
+        target.jump(0x1234_5678_9abc);
+        assert_eq!(distance::<_, 2>(origin, target), Ok(0x1234_5678_9abc));
+        assert_eq!(
+            distance::<_, 2>(origin, Xoroshiro64Star::default()),
+            Err(RngError::ZeroState)
+        );
+    }
+}
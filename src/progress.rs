@@ -0,0 +1,124 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+// returned by solvers and searches stopped through ``Progress::cancel``
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProgressEvent {
+    // baby-step table of the ``index``th of ``count`` primes built
+    TableBuilt {
+        prime: u128,
+        index: usize,
+        count: usize,
+    },
+    // discrete log modulo the ``index``th of ``count`` primes found
+    PrimeSolved {
+        prime: u128,
+        index: usize,
+        count: usize,
+    },
+    // ``done`` of the ``total`` chunks of a parallel brute force finished,
+    // chunks finishing in no particular order
+    ChunkDone {
+        done: u128,
+        total: u128,
+    },
+}
+
+type Callback = Arc<dyn Fn(ProgressEvent) + Send + Sync>;
+
+// shared between the caller and the workers, clones observe the same
+// cancellation
+#[derive(Clone, Default)]
+pub struct Progress {
+    cancelled: Arc<AtomicBool>,
+    callback: Option<Callback>,
+}
+
+impl Progress {
+    pub fn new() -> Progress {
+        Progress::default()
+    }
+
+    pub fn with_callback(callback: impl Fn(ProgressEvent) + Send + Sync + 'static) -> Progress {
+        Progress {
+            cancelled: Arc::default(),
+            callback: Some(Arc::new(callback)),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn report(&self, event: ProgressEvent) {
+        if let Some(callback) = &self.callback {
+            callback(event);
+        }
+    }
+}
+
+// the plain counterpart of a ``_with_progress`` function, which reports to
+// its ``progress`` and stops with ``Cancelled`` once that is cancelled; a
+// fresh progress is never cancelled
+pub fn uncancelled<T>(run: impl FnOnce(&Progress) -> Result<T, Cancelled>) -> T {
+    match run(&Progress::new()) {
+        Ok(value) => value,
+        Err(Cancelled) => unreachable!("a fresh progress is never cancelled"),
+    }
+}
+
+// counts the chunks of one brute force as they finish, reporting each
+pub struct ChunkCounter<'a> {
+    progress: &'a Progress,
+    done: AtomicU64,
+    total: u128,
+}
+
+impl<'a> ChunkCounter<'a> {
+    pub fn new(progress: &'a Progress, total: u128) -> ChunkCounter<'a> {
+        ChunkCounter {
+            progress,
+            done: AtomicU64::new(0),
+            total,
+        }
+    }
+
+    pub fn finish(&self) {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        self.progress.report(ProgressEvent::ChunkDone {
+            done: done as u128,
+            total: self.total,
+        });
+    }
+}
+
+// loops check for cancellation every this many steps
+pub const CHECK_INTERVAL: u128 = 0x1000;
@@ -1,12 +1,31 @@
+use crate::{error::RngError, gf2vec::Exponent};
 use ndarray::Array2;
+use num_traits::Zero;
 
+#[allow(clippy::upper_case_acronyms)]
 pub trait RNG {
-    type AdvanceInt;
+    type AdvanceInt: Exponent;
     type MatrixInt;
     fn next_state(&mut self);
     fn matrix() -> Array2<Self::MatrixInt>;
     fn advance(&mut self, adv: Self::AdvanceInt);
     fn jump(&mut self, jmp: Self::AdvanceInt);
-    fn distance(&mut self, other: Self) -> Self::AdvanceInt;
+    // the all-zero state is the one failure every linear generator shares,
+    // generators override this when they can fail in other ways
+    fn try_jump(&mut self, jmp: Self::AdvanceInt) -> Result<(), RngError> {
+        if self.state().is_zero() {
+            return Err(RngError::ZeroState);
+        }
+        self.jump(jmp);
+        Ok(())
+    }
+    // distances are discrete logs, which only some generators can afford, so
+    // there is no panicking counterpart
+    fn try_distance(&mut self, other: Self) -> Result<Self::AdvanceInt, RngError>
+    where
+        Self: Sized;
     fn state(&mut self) -> Self::AdvanceInt;
+    // states of linear generators add under xor, the sum advancing as the
+    // sum of the advanced states
+    fn add_state(&mut self, other: &Self);
 }
@@ -0,0 +1,390 @@
+use crate::{
+    lcg::{Lcg32, Lcg64},
+    pokemon::{Gender, Pokemon, ShinyType},
+    progress::{uncancelled, Cancelled, ChunkCounter, Progress, CHECK_INTERVAL},
+    rng::RNG,
+};
+use rayon::{current_num_threads, prelude::*};
+use std::ops::Range;
+
+// anything the searcher can step through: jumped ahead to the start of a
+// chunk and then stepped one advance at a time, each result generated from a
+// clone
+pub trait Stepper: Clone + Send + Sync {
+    fn skip(&mut self, advance: u128);
+    fn step(&mut self);
+}
+
+// u128 advances for the 128 bit states, BigUint for xoshiro256 and mt19937
+impl<R> Stepper for R
+where
+    R: RNG + Clone + Send + Sync,
+    R::AdvanceInt: From<u128>,
+{
+    fn skip(&mut self, advance: u128) {
+        self.jump(advance.into());
+    }
+
+    fn step(&mut self) {
+        self.next_state();
+    }
+}
+
+// the lcg periods divide 2 ** 32 and 2 ** 64, so advances truncate
+impl<const MULT: u32, const ADD: u32> Stepper for Lcg32<MULT, ADD> {
+    fn skip(&mut self, advance: u128) {
+        self.advance(advance as u32);
+    }
+
+    fn step(&mut self) {
+        self.next_u32();
+    }
+}
+
+impl<const MULT: u64, const ADD: u64> Stepper for Lcg64<MULT, ADD> {
+    fn skip(&mut self, advance: u128) {
+        self.advance(advance as u64);
+    }
+
+    fn step(&mut self) {
+        self.next_u64();
+    }
+}
+
+// plain counters, for brute forcing every seed of a given width
+impl Stepper for u32 {
+    fn skip(&mut self, advance: u128) {
+        *self = self.wrapping_add(advance as u32);
+    }
+
+    fn step(&mut self) {
+        *self = self.wrapping_add(1);
+    }
+}
+
+pub trait Filter<T>: Sync {
+    fn matches(&self, value: &T) -> bool;
+}
+
+impl<T, F: Fn(&T) -> bool + Sync> Filter<T> for F {
+    fn matches(&self, value: &T) -> bool {
+        self(value)
+    }
+}
+
+// combinators, e.g. And(IvRange { .. }, Or(NatureIn(..), Not(GenderIs(..))))
+pub struct And<A, B>(pub A, pub B);
+pub struct Or<A, B>(pub A, pub B);
+pub struct Not<A>(pub A);
+
+impl<T, A: Filter<T>, B: Filter<T>> Filter<T> for And<A, B> {
+    fn matches(&self, value: &T) -> bool {
+        self.0.matches(value) && self.1.matches(value)
+    }
+}
+
+impl<T, A: Filter<T>, B: Filter<T>> Filter<T> for Or<A, B> {
+    fn matches(&self, value: &T) -> bool {
+        self.0.matches(value) || self.1.matches(value)
+    }
+}
+
+impl<T, A: Filter<T>> Filter<T> for Not<A> {
+    fn matches(&self, value: &T) -> bool {
+        !self.0.matches(value)
+    }
+}
+
+// pokemon filters apply to any result holding a pokemon
+pub struct IvRange {
+    pub min: [u8; 6],
+    pub max: [u8; 6],
+}
+
+pub struct NatureIn(pub Vec<u8>);
+pub struct ShinyIn(pub Vec<ShinyType>);
+pub struct AbilityIs(pub u8);
+pub struct GenderIs(pub Gender);
+
+impl<T: AsRef<Pokemon>> Filter<T> for IvRange {
+    fn matches(&self, value: &T) -> bool {
+        let ivs = value.as_ref().ivs;
+        (0..6).all(|i| (self.min[i]..=self.max[i]).contains(&ivs[i]))
+    }
+}
+
+impl<T: AsRef<Pokemon>> Filter<T> for NatureIn {
+    fn matches(&self, value: &T) -> bool {
+        self.0.contains(&value.as_ref().nature)
+    }
+}
+
+impl<T: AsRef<Pokemon>> Filter<T> for ShinyIn {
+    fn matches(&self, value: &T) -> bool {
+        self.0.contains(&value.as_ref().shiny)
+    }
+}
+
+impl<T: AsRef<Pokemon>> Filter<T> for AbilityIs {
+    fn matches(&self, value: &T) -> bool {
+        value.as_ref().ability == self.0
+    }
+}
+
+impl<T: AsRef<Pokemon>> Filter<T> for GenderIs {
+    fn matches(&self, value: &T) -> bool {
+        value.as_ref().gender == self.0
+    }
+}
+
+// calls ``sink`` with every (advance, result) in ``advances`` from ``origin``
+// passing ``filter`` as they are found, in no particular order; the range is
+// split into chunks that each jump ahead to their start
+pub fn search_with<R, T, G, F, S>(
+    origin: R,
+    advances: Range<u128>,
+    generate: G,
+    filter: &F,
+    sink: S,
+) where
+    R: Stepper,
+    G: Fn(R) -> T + Sync,
+    F: Filter<T>,
+    S: Fn(u128, T) + Sync,
+{
+    uncancelled(|progress| search_with_progress(origin, advances, generate, filter, sink, progress))
+}
+
+// results sunk before a cancellation are kept
+pub fn search_with_progress<R, T, G, F, S>(
+    origin: R,
+    advances: Range<u128>,
+    generate: G,
+    filter: &F,
+    sink: S,
+    progress: &Progress,
+) -> Result<(), Cancelled>
+where
+    R: Stepper,
+    G: Fn(R) -> T + Sync,
+    F: Filter<T>,
+    S: Fn(u128, T) + Sync,
+{
+    let length = advances.end.saturating_sub(advances.start);
+    if length == 0 {
+        return Ok(());
+    }
+    // several chunks per thread to even out the work
+    let chunk_size = length.div_ceil(current_num_threads() as u128 * 4).max(1);
+    let chunk_count = length.div_ceil(chunk_size);
+    let counter = ChunkCounter::new(progress, chunk_count);
+    (0..chunk_count).into_par_iter().try_for_each(|chunk| {
+        let start = advances.start + chunk * chunk_size;
+        let end = (start + chunk_size).min(advances.end);
+        let mut rng = origin.clone();
+        rng.skip(start);
+        for advance in start..end {
+            if (advance - start).is_multiple_of(CHECK_INTERVAL) {
+                progress.check()?;
+            }
+            let result = generate(rng.clone());
+            if filter.matches(&result) {
+                sink(advance, result);
+            }
+            rng.step();
+        }
+        counter.finish();
+        Ok(())
+    })
+}
+
+// every (advance, result) passing ``filter``, sorted by advance
+pub fn search<R, T, G, F>(
+    origin: R,
+    advances: Range<u128>,
+    generate: G,
+    filter: &F,
+) -> Vec<(u128, T)>
+where
+    R: Stepper,
+    T: Send,
+    G: Fn(R) -> T + Sync,
+    F: Filter<T>,
+{
+    let results = std::sync::Mutex::new(vec![]);
+    search_with(origin, advances, generate, filter, |advance, result| {
+        results.lock().unwrap().push((advance, result));
+    });
+    let mut results = results.into_inner().unwrap();
+    results.sort_unstable_by_key(|(advance, _)| *advance);
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        lcg::{BwRng, PokeRng},
+        mt::Mt19937,
+        progress::ProgressEvent,
+        xoroshiro::Xoroshiro128Plus,
+        xoshiro::Xoshiro256Plus,
+    };
+    use num_bigint::BigUint;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn search_steps_lcgs_and_rngs() {
+        // high halves below 0x100 within the first 100000 advances, checked
+        // against stepping one at a time
+        let generate = |mut rng: PokeRng| rng.next_u16();
+        let results = search(PokeRng::new(0), 0..100000, generate, &|value: &u16| {
+            *value < 0x100
+        });
+        let mut rng = PokeRng::new(0);
+        let expected: Vec<(u128, u16)> = (0..100000)
+            .filter_map(|advance| {
+                let value = generate(rng);
+                rng.next_u32();
+                (value < 0x100).then_some((advance, value))
+            })
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(results, expected);
+
+        let results = search(
+            Xoroshiro128Plus::new(0),
+            1000..2000,
+            |mut rng: Xoroshiro128Plus| rng.next_u64(),
+            &Not(|value: &u64| *value >> 56 != 0),
+        );
+        let rng = Xoroshiro128Plus::new(0);
+        for (advance, value) in results {
+            assert!(value >> 56 == 0);
+            let mut expected = rng;
+            expected.jump(advance);
+            assert_eq!(expected.next_u64(), value);
+        }
+    }
+
+    #[test]
+    fn search_steps_wide_states() {
+        let results = search(
+            Xoshiro256Plus::new([1, 2, 3, 4]),
+            500..600,
+            |mut rng: Xoshiro256Plus| rng.next_u64(),
+            &|value: &u64| *value >> 60 == 0,
+        );
+        assert!(!results.is_empty());
+        for (advance, value) in results {
+            let mut expected = Xoshiro256Plus::new([1, 2, 3, 4]);
+            expected.advance(BigUint::from(advance));
+            assert_eq!(expected.next_u64(), value);
+        }
+
+        let results = search(
+            Mt19937::new(5489),
+            1000..1010,
+            |mut rng: Mt19937| rng.next_u32(),
+            &|_: &u32| true,
+        );
+        let mut rng = Mt19937::new(5489);
+        rng.advance(1000);
+        let expected: Vec<(u128, u32)> = (1000..1010)
+            .map(|advance| {
+                let value = rng.clone().next_u32();
+                rng.next_state();
+                (advance, value)
+            })
+            .collect();
+        assert_eq!(results, expected);
+    }
+
+    #[test]
+    fn lcg_searches_wrap_past_their_period() {
+        // advances past 2 ** 32 and 2 ** 64 truncate to the same states
+        let generate = |mut rng: PokeRng| rng.next_u16();
+        let filter = |value: &u16| *value < 0x1000;
+        let wrapped = search(
+            PokeRng::new(0),
+            (1 << 32) + 10..(1 << 32) + 5000,
+            generate,
+            &filter,
+        );
+        let direct = search(PokeRng::new(0), 10..5000, generate, &filter);
+        assert!(!direct.is_empty());
+        assert_eq!(
+            wrapped,
+            direct
+                .iter()
+                .map(|(advance, value)| (advance + (1 << 32), *value))
+                .collect::<Vec<_>>()
+        );
+
+        let generate = |mut rng: BwRng| rng.next_u32();
+        let filter = |value: &u32| *value < 0x1000_0000;
+        let wrapped = search(
+            BwRng::new(1),
+            (1 << 64) + 10..(1 << 64) + 500,
+            generate,
+            &filter,
+        );
+        let direct = search(BwRng::new(1), 10..500, generate, &filter);
+        assert!(!direct.is_empty());
+        assert_eq!(
+            wrapped,
+            direct
+                .iter()
+                .map(|(advance, value)| (advance + (1 << 64), *value))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn search_reports_chunks_and_cancels() {
+        let events = Arc::new(Mutex::new(vec![]));
+        let progress = Progress::with_callback({
+            let events = events.clone();
+            move |event| events.lock().unwrap().push(event)
+        });
+        let generate = |mut rng: PokeRng| rng.next_u16();
+        search_with_progress(
+            PokeRng::new(0),
+            0..100000,
+            generate,
+            &|_: &u16| true,
+            |_, _| {},
+            &progress,
+        )
+        .unwrap();
+        let events = events.lock().unwrap();
+        // chunks may report out of order, but every count up to the total
+        // is reported once
+        let mut done: Vec<u128> = events
+            .iter()
+            .map(|event| match event {
+                ProgressEvent::ChunkDone { done, .. } => *done,
+                event => panic!("unexpected {event:?}"),
+            })
+            .collect();
+        done.sort_unstable();
+        let total = done.len() as u128;
+        assert_eq!(done, (1..=total).collect::<Vec<_>>());
+        assert!(events.iter().all(
+            |event| matches!(event, ProgressEvent::ChunkDone { total: t, .. } if *t == total)
+        ));
+
+        progress.cancel();
+        assert_eq!(
+            search_with_progress(
+                PokeRng::new(0),
+                0..100000,
+                generate,
+                &|_: &u16| true,
+                |_, _| {},
+                &progress
+            ),
+            Err(Cancelled)
+        );
+    }
+}
@@ -0,0 +1 @@
+pub mod tera_raid;
@@ -0,0 +1,282 @@
+use crate::{
+    pokemon::{
+        apply_trainer_shininess, roll_ivs, shiny_type, AbilityType, Gender, Pokemon, ShinyType,
+    },
+    progress::{uncancelled, Cancelled, Progress},
+    search::{self, Filter},
+    xoroshiro::Xoroshiro128Plus,
+};
+use serde::Serialize;
+use std::sync::Mutex;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShinyLock {
+    Random,
+    Always,
+    Never,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Encounter {
+    pub flawless_ivs: u8,
+    pub shiny: ShinyLock,
+    pub ability: AbilityType,
+    // 255 genderless, 254 female only, 0 male only
+    pub gender_ratio: u8,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TeraRaid {
+    pub pokemon: Pokemon,
+    pub height: u8,
+    pub weight: u8,
+    pub scale: u8,
+    pub tera_type: u8,
+}
+
+impl AsRef<Pokemon> for TeraRaid {
+    fn as_ref(&self) -> &Pokemon {
+        &self.pokemon
+    }
+}
+
+// gender is rolled out of 100 against a threshold per ratio
+fn female_threshold(gender_ratio: u8) -> u64 {
+    match gender_ratio {
+        0x1F => 12,
+        0x3F => 25,
+        0x7F => 50,
+        0xBF => 75,
+        0xE1 => 89,
+        ratio => ratio as u64 * 100 / 256,
+    }
+}
+
+// rand(0x81) + rand(0x80), biased towards the middle
+fn size(rng: &mut Xoroshiro128Plus) -> u8 {
+    (rng.rand(0x81) + rng.rand(0x80)) as u8
+}
+
+pub fn generate(seed: u32, encounter: &Encounter, tid: u16, sid: u16) -> TeraRaid {
+    let tidsid = ((sid as u32) << 16) | tid as u32;
+    let mut rng = Xoroshiro128Plus::new(seed as u64);
+    let ec = rng.rand(0xFFFFFFFF) as u32;
+    let fake_tidsid = rng.rand(0xFFFFFFFF) as u32;
+    let mut pid = rng.rand(0xFFFFFFFF) as u32;
+    pid = match encounter.shiny {
+        ShinyLock::Random => apply_trainer_shininess(pid, fake_tidsid, tidsid),
+        ShinyLock::Always => {
+            if shiny_type(pid, fake_tidsid) == ShinyType::None {
+                // square for the fake trainer
+                let low = pid & 0xFFFF;
+                pid = (((fake_tidsid >> 16) ^ fake_tidsid ^ low) << 16) | low;
+            }
+            apply_trainer_shininess(pid, fake_tidsid, tidsid)
+        }
+        ShinyLock::Never => {
+            if shiny_type(pid, tidsid) != ShinyType::None {
+                pid ^= 0x10000000;
+            }
+            pid
+        }
+    };
+    let ivs = roll_ivs(encounter.flawless_ivs, |max| rng.rand(max));
+    let ability = match encounter.ability {
+        AbilityType::Fixed(ability) => ability,
+        AbilityType::NoHidden => rng.rand(2) as u8,
+        AbilityType::Any => rng.rand(3) as u8,
+    };
+    let gender = match encounter.gender_ratio {
+        255 => Gender::Genderless,
+        254 => Gender::Female,
+        0 => Gender::Male,
+        ratio => {
+            if rng.rand(100) < female_threshold(ratio) {
+                Gender::Female
+            } else {
+                Gender::Male
+            }
+        }
+    };
+    let nature = rng.rand(25) as u8;
+    let height = size(&mut rng);
+    let weight = size(&mut rng);
+    let scale = size(&mut rng);
+
+    TeraRaid {
+        pokemon: Pokemon {
+            ec,
+            pid,
+            shiny: shiny_type(pid, tidsid),
+            ivs,
+            ability,
+            gender,
+            nature,
+        },
+        height,
+        weight,
+        scale,
+        // rolled from a separate rng on the same seed
+        tera_type: Xoroshiro128Plus::new(seed as u64).rand(18) as u8,
+    }
+}
+
+// raid-specific filters, combined with the pokemon ones of ``search``, e.g.
+// And(ShinyIn(..), And(IvRange { .. }, TeraTypeIs(..)))
+pub struct TeraTypeIs(pub u8);
+
+pub struct ScaleRange {
+    pub min: u8,
+    pub max: u8,
+}
+
+impl Filter<TeraRaid> for TeraTypeIs {
+    fn matches(&self, raid: &TeraRaid) -> bool {
+        raid.tera_type == self.0
+    }
+}
+
+impl Filter<TeraRaid> for ScaleRange {
+    fn matches(&self, raid: &TeraRaid) -> bool {
+        (self.min..=self.max).contains(&raid.scale)
+    }
+}
+
+// calls ``sink`` with every (seed, raid) matching ``filter`` as they are
+// found, in no particular order
+pub fn search_into<F, S>(encounter: &Encounter, tid: u16, sid: u16, filter: &F, sink: S)
+where
+    F: Filter<TeraRaid>,
+    S: Fn(u32, TeraRaid) + Sync,
+{
+    uncancelled(|progress| search_into_with_progress(encounter, tid, sid, filter, sink, progress))
+}
+
+pub fn search_into_with_progress<F, S>(
+    encounter: &Encounter,
+    tid: u16,
+    sid: u16,
+    filter: &F,
+    sink: S,
+    progress: &Progress,
+) -> Result<(), Cancelled>
+where
+    F: Filter<TeraRaid>,
+    S: Fn(u32, TeraRaid) + Sync,
+{
+    search::search_with_progress(
+        0u32,
+        0..1 << 32,
+        |seed| generate(seed, encounter, tid, sid),
+        filter,
+        |seed, raid| sink(seed as u32, raid),
+        progress,
+    )
+}
+
+// seeds are searched in blocks doubling up to the largest, so that a page
+// stops soon after its last match
+const FIRST_BLOCK: u64 = 1 << 16;
+const LAST_BLOCK: u64 = 1 << 24;
+
+// the first ``limit`` seeds from ``start`` on whose raid matches ``filter``,
+// sorted by seed; the next page starts after the last seed returned
+pub fn search<F: Filter<TeraRaid>>(
+    encounter: &Encounter,
+    tid: u16,
+    sid: u16,
+    filter: &F,
+    start: u32,
+    limit: usize,
+) -> Vec<(u32, TeraRaid)> {
+    uncancelled(|progress| {
+        search_with_progress(encounter, tid, sid, filter, start, limit, progress)
+    })
+}
+
+// each block reports its own run of chunks
+pub fn search_with_progress<F: Filter<TeraRaid>>(
+    encounter: &Encounter,
+    tid: u16,
+    sid: u16,
+    filter: &F,
+    start: u32,
+    limit: usize,
+    progress: &Progress,
+) -> Result<Vec<(u32, TeraRaid)>, Cancelled> {
+    let mut results = vec![];
+    let mut block_start = start as u64;
+    let mut block = FIRST_BLOCK;
+    while results.len() < limit && block_start < 1 << 32 {
+        let block_end = (block_start + block).min(1 << 32);
+        let found = Mutex::new(vec![]);
+        search::search_with_progress(
+            block_start as u32,
+            0..(block_end - block_start) as u128,
+            |seed| generate(seed, encounter, tid, sid),
+            filter,
+            |advance, raid| {
+                let seed = (block_start + advance as u64) as u32;
+                found.lock().unwrap().push((seed, raid));
+            },
+            progress,
+        )?;
+        let mut found = found.into_inner().unwrap();
+        found.sort_unstable_by_key(|(seed, _)| *seed);
+        results.extend(found);
+        block_start = block_end;
+        block = (block * 2).min(LAST_BLOCK);
+    }
+    results.truncate(limit);
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{And, IvRange, Not, ShinyIn};
+
+    #[test]
+    fn raid_filters_combine_with_pokemon_filters() {
+        let encounter = Encounter {
+            flawless_ivs: 1,
+            shiny: ShinyLock::Random,
+            ability: AbilityType::NoHidden,
+            gender_ratio: 0x7F,
+        };
+        let filter = And(
+            And(TeraTypeIs(3), ScaleRange { min: 64, max: 192 }),
+            And(
+                Not(ShinyIn(vec![ShinyType::Star, ShinyType::Square])),
+                IvRange {
+                    min: [0, 0, 0, 0, 0, 0],
+                    max: [31, 31, 31, 31, 31, 15],
+                },
+            ),
+        );
+        let generate = |seed| generate(seed, &encounter, 12345, 54321);
+        let results = search::search(0u32, 0..20000, generate, &filter);
+        let expected: Vec<(u128, TeraRaid)> = (0..20000)
+            .map(|seed| (seed as u128, generate(seed)))
+            .filter(|(_, raid)| {
+                raid.tera_type == 3
+                    && (64..=192).contains(&raid.scale)
+                    && raid.pokemon.shiny == ShinyType::None
+                    && raid.pokemon.ivs[5] <= 15
+            })
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(results, expected);
+
+        // pages of the sink-less search pick up where the last one stopped
+        let page = search(&encounter, 12345, 54321, &filter, 0, 2);
+        let next = search(&encounter, 12345, 54321, &filter, page[1].0 + 1, 1);
+        assert_eq!(
+            page.iter()
+                .chain(&next)
+                .map(|(seed, raid)| (*seed as u128, *raid))
+                .collect::<Vec<_>>(),
+            expected[..3]
+        );
+    }
+}
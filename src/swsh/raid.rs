@@ -0,0 +1,139 @@
+use crate::{
+    pokemon::{
+        apply_trainer_shininess, roll_ivs, rolled_pid_mask, shiny_type, AbilityType, Gender,
+        Pokemon,
+    },
+    progress::{uncancelled, Cancelled, ChunkCounter, Progress},
+    xoroshiro::Xoroshiro128Plus,
+};
+use rayon::prelude::*;
+
+// the den seed advances by the first output every day, i.e. by seed_1
+const DAY_OFFSET: u64 = 0x82A2B175229D6A5B;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Den {
+    pub flawless_ivs: u8,
+    pub ability: AbilityType,
+    // 255 genderless, 254 female only, 0 male only
+    pub gender_ratio: u8,
+}
+
+pub fn generate(seed: u64, den: &Den, tid: u16, sid: u16) -> Pokemon {
+    let tidsid = ((sid as u32) << 16) | tid as u32;
+    let mut rng = Xoroshiro128Plus::new(seed);
+    let ec = rng.rand(0xFFFFFFFF) as u32;
+    let fake_tidsid = rng.rand(0xFFFFFFFF) as u32;
+    let pid = apply_trainer_shininess(rng.rand(0xFFFFFFFF) as u32, fake_tidsid, tidsid);
+    let ivs = roll_ivs(den.flawless_ivs, |max| rng.rand(max));
+
+    let ability = match den.ability {
+        AbilityType::Fixed(ability) => ability,
+        AbilityType::NoHidden => rng.rand(2) as u8,
+        AbilityType::Any => rng.rand(3) as u8,
+    };
+    let gender = match den.gender_ratio {
+        255 => Gender::Genderless,
+        254 => Gender::Female,
+        0 => Gender::Male,
+        ratio => {
+            if (rng.rand(253) + 1) < ratio as u64 {
+                Gender::Female
+            } else {
+                Gender::Male
+            }
+        }
+    };
+    let nature = rng.rand(25) as u8;
+
+    Pokemon {
+        ec,
+        pid,
+        shiny: shiny_type(pid, tidsid),
+        ivs,
+        ability,
+        gender,
+        nature,
+    }
+}
+
+// den seeds generating ``observed``, the pokemon of consecutive days starting
+// from the seed, assuming the 32 bit rolls of the first pokemon were not rerolled
+pub fn recover_seeds(den: &Den, tid: u16, sid: u16, observed: &[Pokemon]) -> Vec<u64> {
+    uncancelled(|progress| recover_seeds_with_progress(den, tid, sid, observed, progress))
+}
+
+// each candidate seed is a chunk
+pub fn recover_seeds_with_progress(
+    den: &Den,
+    tid: u16,
+    sid: u16,
+    observed: &[Pokemon],
+    progress: &Progress,
+) -> Result<Vec<u64>, Cancelled> {
+    let Some(first) = observed.first() else {
+        return Ok(vec![]);
+    };
+    let candidates = Xoroshiro128Plus::seeds_from_outputs(&[
+        (0, first.ec as u64, 0xFFFFFFFF),
+        (2, first.pid as u64, rolled_pid_mask(first)),
+    ]);
+    let counter = ChunkCounter::new(progress, candidates.len() as u128);
+    let seeds: Vec<Option<u64>> = candidates
+        .into_par_iter()
+        .map(|seed| {
+            progress.check()?;
+            let matches = observed.iter().enumerate().all(|(day, pokemon)| {
+                let seed = seed.wrapping_add(DAY_OFFSET.wrapping_mul(day as u64));
+                generate(seed, den, tid, sid) == *pokemon
+            });
+            counter.finish();
+            Ok(matches.then_some(seed))
+        })
+        .collect::<Result<_, Cancelled>>()?;
+    let mut seeds: Vec<u64> = seeds.into_iter().flatten().collect();
+    seeds.sort_unstable();
+    Ok(seeds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEN: Den = Den {
+        flawless_ivs: 4,
+        ability: AbilityType::Any,
+        gender_ratio: 0x7F,
+    };
+
+    #[test]
+    fn day_offset_is_the_first_output() {
+        for seed in [0, 0x0123456789ABCDEF, u64::MAX] {
+            assert_eq!(
+                Xoroshiro128Plus::new(seed).next_u64(),
+                seed.wrapping_add(DAY_OFFSET)
+            );
+        }
+    }
+
+    #[test]
+    fn seeds_are_recovered_from_consecutive_days() {
+        let seed: u64 = 0xB0BA_CAFE_1234_5678;
+        let observed: Vec<Pokemon> = (0..3)
+            .map(|day| {
+                let day_seed = seed.wrapping_add(DAY_OFFSET.wrapping_mul(day));
+                generate(day_seed, &DEN, 12345, 54321)
+            })
+            .collect();
+        assert!(observed[0].ivs.iter().filter(|iv| **iv == 31).count() >= 4);
+        assert_eq!(recover_seeds(&DEN, 12345, 54321, &observed), vec![seed]);
+        assert_eq!(recover_seeds(&DEN, 12345, 54321, &[]), Vec::<u64>::new());
+
+        let progress = Progress::new();
+        progress.cancel();
+        assert_eq!(
+            recover_seeds_with_progress(&DEN, 12345, 54321, &observed, &progress),
+            Err(Cancelled)
+        );
+    }
+}
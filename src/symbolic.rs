@@ -0,0 +1,215 @@
+use crate::gf2int::GF2Int;
+use ndarray::{s, Array2};
+use std::ops::{BitXor, Shl, Shr};
+
+// word operations shared by concrete integers and ``SymbolicWord`` so that a
+// generator's step function can be traced into its transition matrix
+pub trait Word:
+    Clone + BitXor<Output = Self> + Shl<u32, Output = Self> + Shr<u32, Output = Self>
+{
+    const BITS: u32;
+    fn rotate_left(self, n: u32) -> Self;
+    fn rotate_right(self, n: u32) -> Self;
+    fn mask(self, mask: u64) -> Self;
+}
+
+macro_rules! impl_word {
+    ($($int:ty),*) => {
+        $(
+            impl Word for $int {
+                const BITS: u32 = <$int>::BITS;
+
+                fn rotate_left(self, n: u32) -> Self {
+                    <$int>::rotate_left(self, n)
+                }
+
+                fn rotate_right(self, n: u32) -> Self {
+                    <$int>::rotate_right(self, n)
+                }
+
+                fn mask(self, mask: u64) -> Self {
+                    self & (mask as $int)
+                }
+            }
+        )*
+    };
+}
+
+impl_word!(u8, u16, u32, u64);
+
+// word whose bits are linear combinations of the initial state bits, each
+// combination packed into u64 limbs indexed by state bit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SymbolicWord<const BITS: u32> {
+    pub bits: Vec<Vec<u64>>,
+}
+
+impl<const BITS: u32> SymbolicWord<BITS> {
+    // the word occupying state bits ``position..position + BITS``
+    pub fn new(position: usize, total_state_size: usize) -> SymbolicWord<BITS> {
+        let mut word = SymbolicWord::zero(total_state_size);
+        for (i, bit) in word.bits.iter_mut().enumerate() {
+            bit[(position + i) / 64] |= 1 << ((position + i) % 64);
+        }
+        word
+    }
+
+    pub fn zero(total_state_size: usize) -> SymbolicWord<BITS> {
+        SymbolicWord {
+            bits: vec![vec![0; total_state_size.div_ceil(64)]; BITS as usize],
+        }
+    }
+
+    fn limbs(&self) -> usize {
+        self.bits[0].len()
+    }
+
+    // ``(total_state_size, BITS)`` matrix in the layout of ``MatBuilder``
+    pub fn to_matrix(&self, total_state_size: usize) -> Array2<GF2Int> {
+        let mut matrix = Array2::<GF2Int>::zeros((total_state_size, BITS as usize));
+        for (j, bit) in self.bits.iter().enumerate() {
+            for i in 0..total_state_size {
+                matrix[[i, j]] = GF2Int::new((bit[i / 64] >> (i % 64)) as u8);
+            }
+        }
+        matrix
+    }
+}
+
+impl<const BITS: u32> BitXor for SymbolicWord<BITS> {
+    type Output = Self;
+
+    fn bitxor(mut self, rhs: Self) -> Self {
+        for (bit, rhs_bit) in self.bits.iter_mut().zip(rhs.bits) {
+            for (limb, rhs_limb) in bit.iter_mut().zip(rhs_bit) {
+                *limb ^= rhs_limb;
+            }
+        }
+        self
+    }
+}
+
+impl<const BITS: u32> Shl<u32> for SymbolicWord<BITS> {
+    type Output = Self;
+
+    fn shl(mut self, rhs: u32) -> Self {
+        let zero = vec![0; self.limbs()];
+        let rhs = rhs.min(BITS) as usize;
+        self.bits.rotate_right(rhs);
+        for bit in &mut self.bits[..rhs] {
+            bit.clone_from(&zero);
+        }
+        self
+    }
+}
+
+impl<const BITS: u32> Shr<u32> for SymbolicWord<BITS> {
+    type Output = Self;
+
+    fn shr(mut self, rhs: u32) -> Self {
+        let zero = vec![0; self.limbs()];
+        let rhs = rhs.min(BITS) as usize;
+        self.bits.rotate_left(rhs);
+        for bit in self.bits.iter_mut().rev().take(rhs) {
+            bit.clone_from(&zero);
+        }
+        self
+    }
+}
+
+impl<const BITS: u32> Word for SymbolicWord<BITS> {
+    const BITS: u32 = BITS;
+
+    fn rotate_left(mut self, n: u32) -> Self {
+        self.bits.rotate_right((n % BITS) as usize);
+        self
+    }
+
+    fn rotate_right(mut self, n: u32) -> Self {
+        self.bits.rotate_left((n % BITS) as usize);
+        self
+    }
+
+    fn mask(mut self, mask: u64) -> Self {
+        for (i, bit) in self.bits.iter_mut().enumerate() {
+            if (mask >> i) & 1 == 0 {
+                bit.fill(0);
+            }
+        }
+        self
+    }
+}
+
+// runs ``step`` on a symbolic state to get the transition matrix of a
+// generator made of ``WORDS`` words of ``BITS`` bits
+pub fn trace_matrix<const BITS: u32, const WORDS: usize>(
+    step: impl Fn([SymbolicWord<BITS>; WORDS]) -> [SymbolicWord<BITS>; WORDS],
+) -> Array2<GF2Int> {
+    let total_state_size = BITS as usize * WORDS;
+    let state = std::array::from_fn(|i| SymbolicWord::new(i * BITS as usize, total_state_size));
+    let mut matrix = Array2::<GF2Int>::zeros((total_state_size, total_state_size));
+    for (i, word) in step(state).iter().enumerate() {
+        matrix
+            .slice_mut(s![.., i * BITS as usize..(i + 1) * BITS as usize])
+            .assign(&word.to_matrix(total_state_size));
+    }
+    matrix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // marsaglia's xorshift128, written once for both kinds of word
+    fn xorshift128<W: Word>([x, y, z, w]: [W; 4]) -> [W; 4] {
+        let t = x.clone() ^ (x << 11);
+        [
+            y,
+            z,
+            w.clone(),
+            w.clone() ^ (w >> 19) ^ t.clone() ^ (t >> 8),
+        ]
+    }
+
+    fn rotating<W: Word>([a, b]: [W; 2]) -> [W; 2] {
+        [
+            b.clone().rotate_left(3) ^ a.clone().mask(0xF0F0),
+            a.rotate_right(5) ^ (b << 2),
+        ]
+    }
+
+    // the row vector of state bits times ``matrix``, words packed low first
+    fn apply(matrix: &Array2<GF2Int>, state: &[u64], bits: usize) -> Vec<u64> {
+        let row = Array2::from_shape_fn((1, state.len() * bits), |(_, i)| {
+            GF2Int::new((state[i / bits] >> (i % bits)) as u8 & 1)
+        });
+        let mut result = vec![0; state.len()];
+        for (i, bit) in row.dot(matrix).iter().enumerate() {
+            result[i / bits] |= (bit.val as u64) << (i % bits);
+        }
+        result
+    }
+
+    #[test]
+    fn traced_matrices_step_like_the_integers() {
+        let matrix = trace_matrix::<32, 4>(xorshift128);
+        let mut state: [u32; 4] = [123456789, 362436069, 521288629, 88675123];
+        for _ in 0..3 {
+            let traced = apply(&matrix, &state.map(|word| word as u64), 32);
+            state = xorshift128(state);
+            assert_eq!(traced, state.map(|word| word as u64));
+        }
+        // the reference first output of xorshift128
+        assert_eq!(
+            xorshift128([123456789u32, 362436069, 521288629, 88675123])[3],
+            3701687786
+        );
+
+        let matrix = trace_matrix::<16, 2>(rotating);
+        let state: [u16; 2] = [0xBEEF, 0x1357];
+        assert_eq!(
+            apply(&matrix, &state.map(|word| word as u64), 16),
+            rotating(state).map(|word| word as u64)
+        );
+    }
+}
@@ -1,20 +1,85 @@
 use crate::{
-    berkowitz::CharPoly,
+    error::RngError,
+    export::serialize_hex,
+    gf2_system::{affine_columns, solve_sum_constraints, AffineSystem, SumConstraint},
     gf2int::GF2Int,
-    gf2vec::{compute_jump_poly, GF2Vec128},
-    mat_builder::{MatBuilder, MatInverse},
-    pohlig_hellman::pohlig_hellman,
+    gf2vec::{GF2Vec128, GF2Vec64},
+    jump::{jump, jump_application_mat, jump_poly_from_state, jump_with_constants, try_distance},
+    mat_builder::MatInverse,
+    notation::impl_state_notation,
+    observation::{observation_matrix, LinearOutput},
+    pohlig_hellman::PohligHellmanSolver,
+    poly_analysis::charpoly,
+    progress::{uncancelled, Cancelled, ChunkCounter, Progress, CHECK_INTERVAL},
     rng::RNG,
+    symbolic::{trace_matrix, SymbolicWord, Word},
 };
-use ndarray::{concatenate, Array2, Axis};
-use std::ops::BitXorAssign;
+use ndarray::Array2;
+use rayon::{current_num_threads, prelude::*};
+use serde::Serialize;
+use std::{
+    ops::BitXorAssign,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock,
+    },
+};
+
+// prime factorizations of the group orders 2 ** n - 1
+const ORDER_128_PRIMES: [u128; 9] = [3, 5, 17, 257, 641, 65537, 274177, 6700417, 67280421310721];
+const ORDER_64_PRIMES: [u128; 7] = [3, 5, 17, 257, 641, 65537, 6700417];
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Default, Serialize)]
 pub struct Xoroshiro128Plus {
+    #[serde(serialize_with = "serialize_hex")]
+    pub seed_0: u64,
+    #[serde(serialize_with = "serialize_hex")]
+    pub seed_1: u64,
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize)]
+pub struct Xoroshiro128PlusPlus {
+    #[serde(serialize_with = "serialize_hex")]
+    pub seed_0: u64,
+    #[serde(serialize_with = "serialize_hex")]
+    pub seed_1: u64,
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize)]
+pub struct Xoroshiro128StarStar {
+    #[serde(serialize_with = "serialize_hex")]
     pub seed_0: u64,
+    #[serde(serialize_with = "serialize_hex")]
     pub seed_1: u64,
 }
 
+#[derive(Copy, Clone, Debug, Default, Serialize)]
+pub struct Xoroshiro64Star {
+    #[serde(serialize_with = "serialize_hex")]
+    pub seed_0: u32,
+    #[serde(serialize_with = "serialize_hex")]
+    pub seed_1: u32,
+}
+
+// https://xoshiro.di.unimi.it/xoroshiro128plus.c
+// uint64_t next(void) {
+//     const uint64_t s0 = s[0];
+//     uint64_t s1 = s[1];
+//     const uint64_t result = s0 + s1;
+//     s1 ^= s0;
+//     s[0] = rotl(s0, 24) ^ s1 ^ (s1 << 16); // a, b
+//     s[1] = rotl(s1, 37); // c
+//     return result;
+// }
+// the rest of the family only differs in a, b, c and the scrambler
+fn xoroshiro_step<W: Word, const A: u32, const B: u32, const C: u32>([s0, s1]: [W; 2]) -> [W; 2] {
+    let s1 = s1 ^ s0.clone();
+    [
+        s0.rotate_left(A) ^ s1.clone() ^ (s1.clone() << B),
+        s1.rotate_left(C),
+    ]
+}
+
 impl Xoroshiro128Plus {
     pub fn new(seed_0: u64) -> Xoroshiro128Plus {
         Xoroshiro128Plus {
@@ -22,6 +87,180 @@ impl Xoroshiro128Plus {
             seed_1: 0x82A2B175229D6A5B,
         }
     }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.seed_0.wrapping_add(self.seed_1);
+        self.next_state();
+        result
+    }
+
+    // value in 0..max, rerolling outputs masked to the bits of max - 1
+    pub fn rand(&mut self, max: u64) -> u64 {
+        let mask = u64::MAX
+            .checked_shr((max.max(1) - 1).leading_zeros())
+            .unwrap_or(0);
+        loop {
+            let result = self.next_u64() & mask;
+            if result < max.max(1) {
+                return result;
+            }
+        }
+    }
+
+    // inverse of ``next_state``, i.e. of ``matrix()``
+    pub fn previous_state(&mut self) {
+        let s1 = self.seed_1.rotate_right(37);
+        let s0 = (self.seed_0 ^ s1 ^ (s1 << 16)).rotate_right(24);
+        self.seed_0 = s0;
+        self.seed_1 = s1 ^ s0;
+    }
+
+    // (seed, advance) with ``Xoroshiro128Plus::new(seed)`` reaching this state
+    // after ``advance`` steps, searching advances 0..=max_advance backwards
+    // from the state in parallel and returning the smallest advance
+    pub fn recover_seed(&self, max_advance: u128) -> Option<(u64, u128)> {
+        uncancelled(|progress| self.recover_seed_with_progress(max_advance, progress))
+    }
+
+    pub fn recover_seed_with_progress(
+        &self,
+        max_advance: u128,
+        progress: &Progress,
+    ) -> Result<Option<(u64, u128)>, Cancelled> {
+        let seed_1 = Xoroshiro128Plus::new(0).seed_1;
+        let chunk_size = (max_advance / current_num_threads() as u128)
+            .max(1)
+            .saturating_add(1);
+        // chunks cover 0..=max_advance, which has u128::MAX + 1 elements at
+        // the top of the range, so the bounds stay inclusive
+        let chunk_count = max_advance / chunk_size + 1;
+        let counter = ChunkCounter::new(progress, chunk_count);
+        // lowest chunk that found a seed, the chunks past it giving up as
+        // they can only find larger advances
+        let found = AtomicU64::new(u64::MAX);
+        (0..chunk_count)
+            .into_par_iter()
+            .map(|chunk| {
+                let start = chunk * chunk_size;
+                let end = start.saturating_add(chunk_size - 1).min(max_advance);
+                let mut rng = *self;
+                // jumping by the period minus ``start`` steps ``start`` back
+                rng.jump(u128::MAX - start);
+                for advance in start..=end {
+                    if (advance - start).is_multiple_of(CHECK_INTERVAL) {
+                        progress.check()?;
+                        if found.load(Ordering::Relaxed) < chunk as u64 {
+                            return Ok(None);
+                        }
+                    }
+                    if rng.seed_1 == seed_1 {
+                        found.fetch_min(chunk as u64, Ordering::Relaxed);
+                        return Ok(Some((rng.seed_0, advance)));
+                    }
+                    rng.previous_state();
+                }
+                counter.finish();
+                Ok(None)
+            })
+            // the first chunk that found a seed or was cancelled
+            .find_first(|result| !matches!(result, Ok(None)))
+            .unwrap_or(Ok(None))
+    }
+
+    // reference jump() and long_jump(), 2 ** 64 and 2 ** 96 advances
+    pub fn jump_2_64(&mut self) {
+        jump_with_constants::<_, 4>(self, &[0xDF900294D8F554A5, 0x170865DF4B3201FC]);
+    }
+
+    pub fn jump_2_96(&mut self) {
+        jump_with_constants::<_, 4>(self, &[0xD2A98B26625EEE7B, 0xDDDF9B1090AA7AC1]);
+    }
+
+    // every seed for which ``Xoroshiro128Plus::new(seed)`` outputs ``value`` at
+    // the bits set in ``known`` for each (advance, value, known)
+    pub fn seeds_from_outputs(outputs: &[(u128, u64, u64)]) -> Vec<u64> {
+        let identity = Array2::<GF2Int>::eye(128);
+        let observations: Vec<(u128, Vec<usize>)> = outputs
+            .iter()
+            .map(|(advance, _, _)| (*advance, (0..128).collect()))
+            .collect();
+        let words = affine_columns(
+            &observation_matrix(&Xoroshiro128Plus::matrix(), &identity, &observations),
+            Xoroshiro128Plus::new(0).seed_1,
+        );
+        let constraints: Vec<SumConstraint> = outputs
+            .iter()
+            .zip(words.chunks(128))
+            .map(|((_, value, known), words)| SumConstraint {
+                lhs: words[0..64].to_vec(),
+                rhs: words[64..128].to_vec(),
+                value: *value,
+                known: *known,
+                bits: 64 - known.leading_zeros() as usize,
+            })
+            .collect();
+        solve_sum_constraints(AffineSystem::new(), &constraints)
+            .par_iter()
+            .flat_map_iter(|system| system.solutions())
+            .collect()
+    }
+}
+
+impl Xoroshiro128PlusPlus {
+    pub fn new(seed_0: u64, seed_1: u64) -> Xoroshiro128PlusPlus {
+        Xoroshiro128PlusPlus { seed_0, seed_1 }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self
+            .seed_0
+            .wrapping_add(self.seed_1)
+            .rotate_left(17)
+            .wrapping_add(self.seed_0);
+        self.next_state();
+        result
+    }
+
+    pub fn jump_2_64(&mut self) {
+        jump_with_constants::<_, 4>(self, &[0x2BD7A6A6E99C2DDC, 0x0992CCAF6A6FCA05]);
+    }
+
+    pub fn jump_2_96(&mut self) {
+        jump_with_constants::<_, 4>(self, &[0x360FD5F2CF8D5D99, 0x9C6E6877736C46E3]);
+    }
+}
+
+impl Xoroshiro128StarStar {
+    pub fn new(seed_0: u64, seed_1: u64) -> Xoroshiro128StarStar {
+        Xoroshiro128StarStar { seed_0, seed_1 }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.seed_0.wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        self.next_state();
+        result
+    }
+
+    // same engine as xoroshiro128+
+    pub fn jump_2_64(&mut self) {
+        jump_with_constants::<_, 4>(self, &[0xDF900294D8F554A5, 0x170865DF4B3201FC]);
+    }
+
+    pub fn jump_2_96(&mut self) {
+        jump_with_constants::<_, 4>(self, &[0xD2A98B26625EEE7B, 0xDDDF9B1090AA7AC1]);
+    }
+}
+
+impl Xoroshiro64Star {
+    pub fn new(seed_0: u32, seed_1: u32) -> Xoroshiro64Star {
+        Xoroshiro64Star { seed_0, seed_1 }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let result = self.seed_0.wrapping_mul(0x9E3779BB);
+        self.next_state();
+        result
+    }
 }
 
 impl BitXorAssign for Xoroshiro128Plus {
@@ -31,37 +270,59 @@ impl BitXorAssign for Xoroshiro128Plus {
     }
 }
 
+impl BitXorAssign for Xoroshiro128PlusPlus {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.seed_0 ^= rhs.seed_0;
+        self.seed_1 ^= rhs.seed_1;
+    }
+}
+
+impl BitXorAssign for Xoroshiro128StarStar {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.seed_0 ^= rhs.seed_0;
+        self.seed_1 ^= rhs.seed_1;
+    }
+}
+
+impl BitXorAssign for Xoroshiro64Star {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.seed_0 ^= rhs.seed_0;
+        self.seed_1 ^= rhs.seed_1;
+    }
+}
+
+impl_state_notation!(Xoroshiro128Plus { seed_0, seed_1 }: u64);
+impl_state_notation!(Xoroshiro128PlusPlus { seed_0, seed_1 }: u64);
+impl_state_notation!(Xoroshiro128StarStar { seed_0, seed_1 }: u64);
+impl_state_notation!(Xoroshiro64Star { seed_0, seed_1 }: u32);
+
+// berkowitz dominates short jumps, so each engine's characteristic polynomial
+// is computed once
+fn xoroshiro128_charpoly() -> GF2Vec128 {
+    static CHAR_POLY: OnceLock<GF2Vec128> = OnceLock::new();
+    *CHAR_POLY.get_or_init(charpoly::<Xoroshiro128Plus, 4>)
+}
+
+fn xoroshiro128_plus_plus_charpoly() -> GF2Vec128 {
+    static CHAR_POLY: OnceLock<GF2Vec128> = OnceLock::new();
+    *CHAR_POLY.get_or_init(charpoly::<Xoroshiro128PlusPlus, 4>)
+}
+
+fn xoroshiro64_charpoly() -> GF2Vec64 {
+    static CHAR_POLY: OnceLock<GF2Vec64> = OnceLock::new();
+    *CHAR_POLY.get_or_init(charpoly::<Xoroshiro64Star, 2>)
+}
+
 impl RNG for Xoroshiro128Plus {
-    // https://xoshiro.di.unimi.it/xoroshiro128plus.c
-    // uint64_t next(void) {
-    //     const uint64_t s0 = s[0];
-    //     uint64_t s1 = s[1];
-    //     const uint64_t result = s0 + s1;
-    //     s1 ^= s0;
-    //     s[0] = rotl(s0, 24) ^ s1 ^ (s1 << 16); // a, b
-    //     s[1] = rotl(s1, 37); // c
-    //     return result;
-    // }
     type AdvanceInt = u128;
     type MatrixInt = GF2Int;
 
     fn next_state(&mut self) {
-        let s0 = self.seed_0;
-        let mut s1 = self.seed_1;
-        // let result = s0 + s1
-        s1 ^= s0;
-        self.seed_0 = s0.rotate_left(24) ^ s1 ^ (s1 << 16);
-        self.seed_1 = s1.rotate_left(37);
+        [self.seed_0, self.seed_1] = xoroshiro_step::<_, 24, 16, 37>([self.seed_0, self.seed_1]);
     }
 
     fn matrix() -> Array2<Self::MatrixInt> {
-        let mut s0_mat = MatBuilder::new(0, 64, 128);
-        let mut s1_mat = MatBuilder::new(64, 64, 128);
-        s1_mat ^= s0_mat.to_owned();
-        s0_mat = s0_mat.rotate_left(24) ^ s1_mat.to_owned() ^ (s1_mat.to_owned() << 16);
-        s1_mat.rotate_left_assign(37);
-
-        concatenate![Axis(1), s0_mat.inner_matrix, s1_mat.inner_matrix]
+        trace_matrix::<64, 2>(xoroshiro_step::<_, 24, 16, 37>)
     }
 
     fn advance(&mut self, adv: Self::AdvanceInt) {
@@ -71,70 +332,423 @@ impl RNG for Xoroshiro128Plus {
     }
 
     fn jump(&mut self, jmp: Self::AdvanceInt) {
-        // can be precomputed
-        let mut char_poly = Xoroshiro128Plus::matrix().compute_charpoly_coeffs();
-        char_poly.reverse();
-        let char_poly = GF2Vec128::new(char_poly);
-        // can be precomputed via jump table
-        let jump_poly = compute_jump_poly(jmp, char_poly);
-        let mut final_state = Xoroshiro128Plus {
-            seed_0: 0,
-            seed_1: 0,
-        };
-        for bit in 0..128 {
-            if ((jump_poly.state_low >> bit) & 1) != 0 {
-                final_state ^= *self;
-            }
+        jump(self, jmp, xoroshiro128_charpoly());
+    }
+
+    fn try_distance(&mut self, other: Self) -> Result<Self::AdvanceInt, RngError> {
+        try_distance(
+            *self,
+            other,
+            xoroshiro128_charpoly(),
+            u128::MAX,
+            ORDER_128_PRIMES.to_vec(),
+        )
+    }
+
+    fn state(&mut self) -> Self::AdvanceInt {
+        (self.seed_0 as u128) | ((self.seed_1 as u128) << 64u128)
+    }
+
+    fn add_state(&mut self, other: &Self) {
+        *self ^= *other;
+    }
+}
+
+impl RNG for Xoroshiro128PlusPlus {
+    type AdvanceInt = u128;
+    type MatrixInt = GF2Int;
+
+    fn next_state(&mut self) {
+        [self.seed_0, self.seed_1] = xoroshiro_step::<_, 49, 21, 28>([self.seed_0, self.seed_1]);
+    }
+
+    fn matrix() -> Array2<Self::MatrixInt> {
+        trace_matrix::<64, 2>(xoroshiro_step::<_, 49, 21, 28>)
+    }
+
+    fn advance(&mut self, adv: Self::AdvanceInt) {
+        for _ in 0..adv {
             self.next_state();
         }
-        self.seed_0 = final_state.seed_0;
-        self.seed_1 = final_state.seed_1;
-    }
-
-    fn distance(&mut self, other: Self) -> Self::AdvanceInt {
-        // can be precomputed
-        let mut char_poly = Xoroshiro128Plus::matrix().compute_charpoly_coeffs();
-        char_poly.reverse();
-        let char_poly = GF2Vec128::new(char_poly);
-
-        let mut start = self.clone();
-        let mut end = other.clone();
-        let mut jump_application_mat = Array2::<GF2Int>::zeros((128, 128));
-        for i in 0..128 {
-            let state = start.state();
-            for j in 0..128 {
-                jump_application_mat[[i, j]] = GF2Int::new(((state >> j) & 1) as u8);
-            }
-            start.next_state();
-        }
-        let jump_application_mat_inv = jump_application_mat.inverse();
-        let mut jump_poly_mat = Array2::<GF2Int>::zeros((1, 128));
-        let end_state = end.state();
-        for i in 0..128 {
-            jump_poly_mat[[0, i]] = GF2Int::new(((end_state >> i) & 1) as u8);
-        }
-        jump_poly_mat = jump_poly_mat.dot(&jump_application_mat_inv);
-        let mut jump_poly = vec![];
-        for i in 0..jump_poly_mat.shape()[1] {
-            jump_poly.push(jump_poly_mat[[0, i]]);
+    }
+
+    fn jump(&mut self, jmp: Self::AdvanceInt) {
+        jump(self, jmp, xoroshiro128_plus_plus_charpoly());
+    }
+
+    fn try_distance(&mut self, other: Self) -> Result<Self::AdvanceInt, RngError> {
+        try_distance(
+            *self,
+            other,
+            xoroshiro128_plus_plus_charpoly(),
+            u128::MAX,
+            ORDER_128_PRIMES.to_vec(),
+        )
+    }
+
+    fn state(&mut self) -> Self::AdvanceInt {
+        (self.seed_0 as u128) | ((self.seed_1 as u128) << 64u128)
+    }
+
+    fn add_state(&mut self, other: &Self) {
+        *self ^= *other;
+    }
+}
+
+impl RNG for Xoroshiro128StarStar {
+    type AdvanceInt = u128;
+    type MatrixInt = GF2Int;
+
+    fn next_state(&mut self) {
+        [self.seed_0, self.seed_1] = xoroshiro_step::<_, 24, 16, 37>([self.seed_0, self.seed_1]);
+    }
+
+    fn matrix() -> Array2<Self::MatrixInt> {
+        trace_matrix::<64, 2>(xoroshiro_step::<_, 24, 16, 37>)
+    }
+
+    fn advance(&mut self, adv: Self::AdvanceInt) {
+        for _ in 0..adv {
+            self.next_state();
         }
-        let jump_poly = GF2Vec128::new(jump_poly);
-        pohlig_hellman(
-            GF2Vec128 {
-                state_low: 0b10,
-                state_high: 0,
-            },
-            // -1 ≡ mod 2**128 - 2 (mod 2**128 - 1)
-            compute_jump_poly(0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFE, char_poly),
-            jump_poly,
-            char_poly,
-            0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF,
-            // precomputed prime-factorization due to integer size limits
-            vec![3, 5, 17, 257, 641, 65537, 274177, 6700417, 67280421310721],
+    }
+
+    fn jump(&mut self, jmp: Self::AdvanceInt) {
+        jump(self, jmp, xoroshiro128_charpoly());
+    }
+
+    fn try_distance(&mut self, other: Self) -> Result<Self::AdvanceInt, RngError> {
+        try_distance(
+            *self,
+            other,
+            xoroshiro128_charpoly(),
+            u128::MAX,
+            ORDER_128_PRIMES.to_vec(),
         )
     }
 
     fn state(&mut self) -> Self::AdvanceInt {
         (self.seed_0 as u128) | ((self.seed_1 as u128) << 64u128)
     }
+
+    fn add_state(&mut self, other: &Self) {
+        *self ^= *other;
+    }
+}
+
+impl RNG for Xoroshiro64Star {
+    type AdvanceInt = u128;
+    type MatrixInt = GF2Int;
+
+    fn next_state(&mut self) {
+        [self.seed_0, self.seed_1] = xoroshiro_step::<_, 26, 9, 13>([self.seed_0, self.seed_1]);
+    }
+
+    fn matrix() -> Array2<Self::MatrixInt> {
+        trace_matrix::<32, 2>(xoroshiro_step::<_, 26, 9, 13>)
+    }
+
+    fn advance(&mut self, adv: Self::AdvanceInt) {
+        for _ in 0..adv {
+            self.next_state();
+        }
+    }
+
+    fn jump(&mut self, jmp: Self::AdvanceInt) {
+        jump(self, jmp, xoroshiro64_charpoly());
+    }
+
+    fn try_distance(&mut self, other: Self) -> Result<Self::AdvanceInt, RngError> {
+        try_distance(
+            *self,
+            other,
+            xoroshiro64_charpoly(),
+            u64::MAX as u128,
+            ORDER_64_PRIMES.to_vec(),
+        )
+    }
+
+    fn state(&mut self) -> Self::AdvanceInt {
+        (self.seed_0 as u128) | ((self.seed_1 as u128) << 32u128)
+    }
+
+    fn add_state(&mut self, other: &Self) {
+        *self ^= *other;
+    }
+}
+
+impl LinearOutput for Xoroshiro128Plus {
+    // s0 + s1 without carries, only bit 0 matches the real output
+    fn output_matrix() -> Array2<Self::MatrixInt> {
+        (SymbolicWord::<64>::new(0, 128) ^ SymbolicWord::new(64, 128)).to_matrix(128)
+    }
+}
+
+// answers ``distance`` queries from a fixed origin state, sharing the inverted
+// jump application matrix and the baby-step tables between targets
+pub struct DistanceSolver {
+    jump_application_mat_inv: Array2<GF2Int>,
+    pohlig_hellman_solver: PohligHellmanSolver<u128, 4>,
+}
+
+impl DistanceSolver {
+    pub fn new(origin: Xoroshiro128Plus) -> DistanceSolver {
+        DistanceSolver::try_new(origin).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_new(mut origin: Xoroshiro128Plus) -> Result<DistanceSolver, RngError> {
+        if origin.state() == 0 {
+            return Err(RngError::ZeroState);
+        }
+        let char_poly = xoroshiro128_charpoly();
+        let jump_application_mat_inv = jump_application_mat(origin, 128).try_inverse()?;
+        let z = GF2Vec128::Z;
+        let pohlig_hellman_solver = PohligHellmanSolver::new_with_progress(
+            z,
+            // z ** -1
+            z.try_inverse_mod(char_poly)?,
+            char_poly,
+            u128::MAX,
+            ORDER_128_PRIMES.to_vec(),
+            &Progress::new(),
+        )?;
+        Ok(DistanceSolver {
+            jump_application_mat_inv,
+            pohlig_hellman_solver,
+        })
+    }
+
+    pub fn distance_to(&self, target: Xoroshiro128Plus) -> u128 {
+        self.try_distance_to(target)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_distance_to(&self, mut target: Xoroshiro128Plus) -> Result<u128, RngError> {
+        if target.state() == 0 {
+            return Err(RngError::ZeroState);
+        }
+        let jump_poly = jump_poly_from_state(&self.jump_application_mat_inv, target);
+        self.pohlig_hellman_solver.try_solve(jump_poly)
+    }
+
+    // one result per target, so a single bad target does not lose the rest
+    pub fn distances_to(&self, targets: &[Xoroshiro128Plus]) -> Vec<Result<u128, RngError>> {
+        targets
+            .par_iter()
+            .map(|target| self.try_distance_to(*target))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // reference outputs of prng.di.unimi.it's xoroshiro128{plus,plusplus,
+    // starstar}.c and xoroshiro64star.c seeded with s = {1, 2}
+
+    #[test]
+    fn next_matches_reference() {
+        let mut plus = Xoroshiro128Plus {
+            seed_0: 1,
+            seed_1: 2,
+        };
+        let mut plus_plus = Xoroshiro128PlusPlus::new(1, 2);
+        let mut star_star = Xoroshiro128StarStar::new(1, 2);
+        let mut star = Xoroshiro64Star::new(1, 2);
+        let outputs: Vec<[u64; 4]> = (0..4)
+            .map(|_| {
+                [
+                    plus.next_u64(),
+                    plus_plus.next_u64(),
+                    star_star.next_u64(),
+                    star.next_u32() as u64,
+                ]
+            })
+            .collect();
+        assert_eq!(
+            outputs,
+            [
+                [
+                    0x0000000000000003,
+                    0x0000000000060001,
+                    0x0000000000001680,
+                    0x9E3779BB
+                ],
+                [
+                    0x0000006001030003,
+                    0x000260C000660007,
+                    0x00000016C3804380,
+                    0x1380CF31
+                ],
+                [
+                    0x20C102C302000C03,
+                    0x180ACC04718606D3,
+                    0x86B5B3AD00004380,
+                    0xF233F6B9
+                ],
+                [
+                    0x810180670D23AD61,
+                    0x9E226D35036FC4C7,
+                    0x800044A4CD1497B2,
+                    0xFDE6B3B9
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn jump_and_long_jump_match_reference() {
+        let mut plus = Xoroshiro128Plus {
+            seed_0: 1,
+            seed_1: 2,
+        };
+        plus.advance(4);
+        plus.jump_2_64();
+        assert_eq!(
+            [plus.seed_0, plus.seed_1],
+            [0xE1807E750BFDCB60, 0xC69157985731D616]
+        );
+        plus.jump_2_96();
+        assert_eq!(
+            [plus.seed_0, plus.seed_1],
+            [0x26904B74AF79E1DF, 0x90FA3DD83CFF016A]
+        );
+
+        let mut plus_plus = Xoroshiro128PlusPlus::new(1, 2);
+        plus_plus.advance(4);
+        plus_plus.jump_2_64();
+        assert_eq!(
+            [plus_plus.seed_0, plus_plus.seed_1],
+            [0xBA74DBB7BE94DF80, 0x574A51C7BEB4A93D]
+        );
+        plus_plus.jump_2_96();
+        assert_eq!(
+            [plus_plus.seed_0, plus_plus.seed_1],
+            [0x5D6247B3FBBEC059, 0x05B19A89A381AE91]
+        );
+
+        let mut star_star = Xoroshiro128StarStar::new(1, 2);
+        star_star.advance(4);
+        star_star.jump(1 << 64);
+        star_star.jump(1 << 96);
+        assert_eq!(
+            [star_star.seed_0, star_star.seed_1],
+            [0x26904B74AF79E1DF, 0x90FA3DD83CFF016A]
+        );
+    }
+
+    #[test]
+    fn generic_jump_matches_reference_jumps() {
+        let mut jumped = Xoroshiro128PlusPlus::new(1, 2);
+        let mut reference = jumped;
+        jumped.jump(1 << 64);
+        reference.jump_2_64();
+        assert_eq!(
+            [jumped.seed_0, jumped.seed_1],
+            [reference.seed_0, reference.seed_1]
+        );
+
+        let mut jumped = Xoroshiro64Star::new(1, 2);
+        let mut advanced = jumped;
+        jumped.jump(12345);
+        advanced.advance(12345);
+        assert_eq!(
+            [jumped.seed_0, jumped.seed_1],
+            [advanced.seed_0, advanced.seed_1]
+        );
+    }
+
+    #[test]
+    fn all_zero_states_are_errors() {
+        let mut zero = Xoroshiro128Plus::default();
+        assert_eq!(zero.try_jump(5), Err(RngError::ZeroState));
+        assert_eq!(
+            Xoroshiro64Star::new(1, 2).try_distance(Xoroshiro64Star::default()),
+            Err(RngError::ZeroState)
+        );
+        assert!(matches!(
+            DistanceSolver::try_new(zero),
+            Err(RngError::ZeroState)
+        ));
+    }
+
+    #[test]
+    fn distance_inverts_jump() {
+        // the 64 bit engine is covered by the shared jump tests
+        let mut origin = Xoroshiro128Plus::new(0x1234);
+        let mut target = origin;
+        target.jump(0xdeadbeef123456789abc);
+        assert_eq!(origin.try_distance(target), Ok(0xdeadbeef123456789abc));
+    }
+
+    // the solver builds every baby-step table up front, the largest having
+    // 2 ** 23 entries, which takes minutes without optimizations
+    #[test]
+    #[ignore = "slow in debug builds, run with --release -- --ignored"]
+    fn distance_solver_batches_targets() {
+        let origin = Xoroshiro128Plus::new(0x1234);
+        let solver = DistanceSolver::new(origin);
+        let mut far = origin;
+        far.jump_2_64();
+        far.jump_2_96();
+        let mut near = origin;
+        for _ in 0..10 {
+            near.next_u64();
+        }
+        let mut behind = origin;
+        behind.previous_state();
+        let zero = Xoroshiro128Plus {
+            seed_0: 0,
+            seed_1: 0,
+        };
+        assert_eq!(
+            solver.distances_to(&[far, near, origin, zero, behind]),
+            vec![
+                Ok((1 << 64) + (1 << 96)),
+                Ok(10),
+                Ok(0),
+                Err(RngError::ZeroState),
+                Ok(u128::MAX - 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn recover_seed_finds_the_advance() {
+        let mut rng = Xoroshiro128Plus::new(0x8badf00d);
+        for _ in 0..1000 {
+            rng.next_u64();
+        }
+        assert_eq!(rng.recover_seed(5000), Some((0x8badf00d, 1000)));
+        assert_eq!(rng.recover_seed(1000), Some((0x8badf00d, 1000)));
+        assert_eq!(rng.recover_seed(999), None);
+        // the range is inclusive, so searching every advance cannot overflow
+        assert_eq!(rng.recover_seed(u128::MAX), Some((0x8badf00d, 1000)));
+
+        let progress = Progress::new();
+        progress.cancel();
+        assert_eq!(
+            rng.recover_seed_with_progress(5000, &progress),
+            Err(Cancelled)
+        );
+    }
+
+    #[test]
+    fn recover_seed_stops_the_later_chunks() {
+        let mut rng = Xoroshiro128Plus::new(0x8badf00d);
+        for _ in 0..1000 {
+            rng.next_u64();
+        }
+        // the chunks past the first would each step 2 ** 38 states back if
+        // they did not give up once the first chunk found the seed
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        assert_eq!(
+            pool.install(|| rng.recover_seed(1 << 40)),
+            Some((0x8badf00d, 1000))
+        );
+    }
 }
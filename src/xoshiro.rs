@@ -0,0 +1,331 @@
+use crate::{
+    berlekamp_massey::{minimal_polynomial, output_sequence},
+    error::RngError,
+    export::serialize_hex_array,
+    gf2int::GF2Int,
+    gf2vec::GF2Vec256,
+    jump::{jump, jump_with_constants, try_distance},
+    notation::impl_state_notation,
+    poly_analysis::group_order,
+    rng::RNG,
+    symbolic::{trace_matrix, Word},
+};
+use ndarray::Array2;
+use num_bigint::BigUint;
+use serde::Serialize;
+use std::sync::OnceLock;
+
+// prime factorization of the group order 2 ** 256 - 1, whose two largest
+// primes put distances beyond baby-step giant-step
+const ORDER_256_PRIMES: [u128; 11] = [
+    3,
+    5,
+    17,
+    257,
+    641,
+    65537,
+    274177,
+    6700417,
+    67280421310721,
+    59649589127497217,
+    5704689200685129054721,
+];
+
+#[derive(Copy, Clone, Debug, Default, Serialize)]
+pub struct Xoshiro256Plus {
+    #[serde(serialize_with = "serialize_hex_array")]
+    pub seed: [u64; 4],
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize)]
+pub struct Xoshiro256PlusPlus {
+    #[serde(serialize_with = "serialize_hex_array")]
+    pub seed: [u64; 4],
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize)]
+pub struct Xoshiro256StarStar {
+    #[serde(serialize_with = "serialize_hex_array")]
+    pub seed: [u64; 4],
+}
+
+// https://prng.di.unimi.it/xoshiro256plus.c
+// uint64_t next(void) {
+//     const uint64_t result = s[0] + s[3];
+//     const uint64_t t = s[1] << 17;
+//     s[2] ^= s[0];
+//     s[3] ^= s[1];
+//     s[1] ^= s[2];
+//     s[0] ^= s[3];
+//     s[2] ^= t;
+//     s[3] = rotl(s[3], 45);
+//     return result;
+// }
+fn xoshiro256_step<W: Word>([s0, s1, s2, s3]: [W; 4]) -> [W; 4] {
+    let t = s1.clone() << 17;
+    let s2 = s2 ^ s0.clone();
+    let s3 = s3 ^ s1.clone();
+    let s1 = s1 ^ s2.clone();
+    let s0 = s0 ^ s3.clone();
+    [s0, s1, s2 ^ t, s3.rotate_left(45)]
+}
+
+const JUMP_2_128: [u64; 4] = [
+    0x180EC6D33CFD0ABA,
+    0xD5A61266F0C9392C,
+    0xA9582618E03FC9AA,
+    0x39ABDC4529B1661C,
+];
+const JUMP_2_192: [u64; 4] = [
+    0x76E15D3EFEFDCBBF,
+    0xC5004E441C522FB3,
+    0x77710069854EE241,
+    0x39109BB02ACBE635,
+];
+
+pub fn xoshiro256_matrix() -> Array2<GF2Int> {
+    trace_matrix::<64, 4>(xoshiro256_step)
+}
+
+// berkowitz on the 256 x 256 matrix is too slow, the characteristic
+// polynomial is primitive so it is also the minimal polynomial of any
+// nonzero output bit sequence
+fn xoshiro256_charpoly() -> GF2Vec256 {
+    static CHAR_POLY: OnceLock<GF2Vec256> = OnceLock::new();
+    *CHAR_POLY.get_or_init(|| {
+        let mut rng = Xoshiro256Plus::new([1, 0, 0, 0]);
+        minimal_polynomial(&output_sequence(&mut rng, 512, |rng| {
+            GF2Int::new((rng.seed[0] & 1) as u8)
+        }))
+    })
+}
+
+fn xoshiro256_state(seed: &[u64; 4]) -> BigUint {
+    BigUint::from_slice(
+        &seed
+            .iter()
+            .flat_map(|word| [*word as u32, (word >> 32) as u32])
+            .collect::<Vec<u32>>(),
+    )
+}
+
+fn order_256_primes() -> Vec<BigUint> {
+    ORDER_256_PRIMES
+        .iter()
+        .map(|prime| BigUint::from(*prime))
+        .collect()
+}
+
+macro_rules! impl_xoshiro256_rng {
+    ($rng:ty) => {
+        impl RNG for $rng {
+            type AdvanceInt = BigUint;
+            type MatrixInt = GF2Int;
+
+            fn next_state(&mut self) {
+                self.seed = xoshiro256_step(self.seed);
+            }
+
+            fn matrix() -> Array2<Self::MatrixInt> {
+                xoshiro256_matrix()
+            }
+
+            fn advance(&mut self, adv: Self::AdvanceInt) {
+                let mut remaining = adv;
+                while remaining.bits() > 0 {
+                    self.next_state();
+                    remaining -= 1u32;
+                }
+            }
+
+            fn jump(&mut self, jmp: Self::AdvanceInt) {
+                jump(self, jmp, xoshiro256_charpoly());
+            }
+
+            // only ever fails, with ``TooLarge`` past the all-zero states
+            fn try_distance(&mut self, other: Self) -> Result<Self::AdvanceInt, RngError> {
+                try_distance(
+                    *self,
+                    other,
+                    xoshiro256_charpoly(),
+                    group_order(256),
+                    order_256_primes(),
+                )
+            }
+
+            fn state(&mut self) -> Self::AdvanceInt {
+                xoshiro256_state(&self.seed)
+            }
+
+            fn add_state(&mut self, other: &Self) {
+                for (word, other_word) in self.seed.iter_mut().zip(other.seed) {
+                    *word ^= other_word;
+                }
+            }
+        }
+    };
+}
+
+impl_xoshiro256_rng!(Xoshiro256Plus);
+impl_xoshiro256_rng!(Xoshiro256PlusPlus);
+impl_xoshiro256_rng!(Xoshiro256StarStar);
+
+impl Xoshiro256Plus {
+    pub fn new(seed: [u64; 4]) -> Xoshiro256Plus {
+        Xoshiro256Plus { seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.seed[0].wrapping_add(self.seed[3]);
+        self.next_state();
+        result
+    }
+
+    // reference jump() and long_jump(), 2 ** 128 and 2 ** 192 advances
+    pub fn jump_2_128(&mut self) {
+        jump_with_constants::<_, 8>(self, &JUMP_2_128);
+    }
+
+    pub fn jump_2_192(&mut self) {
+        jump_with_constants::<_, 8>(self, &JUMP_2_192);
+    }
+}
+
+impl Xoshiro256PlusPlus {
+    pub fn new(seed: [u64; 4]) -> Xoshiro256PlusPlus {
+        Xoshiro256PlusPlus { seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.seed[0]
+            .wrapping_add(self.seed[3])
+            .rotate_left(23)
+            .wrapping_add(self.seed[0]);
+        self.next_state();
+        result
+    }
+
+    pub fn jump_2_128(&mut self) {
+        jump_with_constants::<_, 8>(self, &JUMP_2_128);
+    }
+
+    pub fn jump_2_192(&mut self) {
+        jump_with_constants::<_, 8>(self, &JUMP_2_192);
+    }
+}
+
+impl Xoshiro256StarStar {
+    pub fn new(seed: [u64; 4]) -> Xoshiro256StarStar {
+        Xoshiro256StarStar { seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.seed[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        self.next_state();
+        result
+    }
+
+    pub fn jump_2_128(&mut self) {
+        jump_with_constants::<_, 8>(self, &JUMP_2_128);
+    }
+
+    pub fn jump_2_192(&mut self) {
+        jump_with_constants::<_, 8>(self, &JUMP_2_192);
+    }
+}
+
+impl_state_notation!(Xoshiro256Plus [u64; 4]);
+impl_state_notation!(Xoshiro256PlusPlus [u64; 4]);
+impl_state_notation!(Xoshiro256StarStar [u64; 4]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // reference outputs of prng.di.unimi.it's xoshiro256{plus,plusplus,
+    // starstar}.c seeded with s = {1, 2, 3, 4}
+    const SEED: [u64; 4] = [1, 2, 3, 4];
+    const JUMPED: [u64; 4] = [
+        0xB2017C44E4DB6616,
+        0xD0788D451750C894,
+        0xA46F2EA159A09D43,
+        0x1BA07983EFE5E6D8,
+    ];
+    // long_jump() after jump() and one more output
+    const LONG_JUMPED: [u64; 4] = [
+        0xD8B5405C1E6DFB74,
+        0x19684424CA9496C3,
+        0xC00BB7B30BF18973,
+        0xB77945A35F2DF11A,
+    ];
+
+    #[test]
+    fn next_u64_matches_reference() {
+        let mut plus = Xoshiro256Plus::new(SEED);
+        let mut plus_plus = Xoshiro256PlusPlus::new(SEED);
+        let mut star_star = Xoshiro256StarStar::new(SEED);
+        let outputs: Vec<[u64; 3]> = (0..4)
+            .map(|_| [plus.next_u64(), plus_plus.next_u64(), star_star.next_u64()])
+            .collect();
+        assert_eq!(
+            outputs,
+            [
+                [0x0000000000000005, 0x0000000002800001, 0x0000000000002D00],
+                [0x0000C00000000007, 0x0000000003800067, 0x0000000000000000],
+                [0x0000C00018000007, 0x000CC00003800067, 0x000000005A007080],
+                [0x8001600018040302, 0x000CC201994400B2, 0x10E0000000009D80],
+            ]
+        );
+    }
+
+    #[test]
+    fn jump_and_long_jump_match_reference() {
+        let mut plus = Xoshiro256Plus::new(SEED);
+        plus.advance(BigUint::from(4u32));
+        plus.jump_2_128();
+        assert_eq!(plus.seed, JUMPED);
+        assert_eq!(plus.next_u64(), 0xCDA1F5C8D4C14CEE);
+        plus.jump_2_192();
+        assert_eq!(plus.seed, LONG_JUMPED);
+        assert_eq!(plus.next_u64(), 0x902E85FF7D9BEC8E);
+
+        let mut plus_plus = Xoshiro256PlusPlus::new(JUMPED);
+        assert_eq!(plus_plus.next_u64(), 0x966BDCEB5C423710);
+        plus_plus.jump_2_192();
+        assert_eq!(plus_plus.next_u64(), 0xD8740E5265B612B6);
+
+        let mut star_star = Xoshiro256StarStar::new(JUMPED);
+        assert_eq!(star_star.next_u64(), 0x986A928C99A10251);
+        star_star.jump_2_192();
+        assert_eq!(star_star.next_u64(), 0xA9FD3BCE0F4025B7);
+    }
+
+    #[test]
+    fn generic_jump_matches_reference_jumps() {
+        let mut rng = Xoshiro256StarStar::new(SEED);
+        rng.advance(BigUint::from(4u32));
+        rng.jump(BigUint::from(1u32) << 128);
+        assert_eq!(rng.seed, JUMPED);
+        rng.next_state();
+        rng.jump(BigUint::from(1u32) << 192);
+        assert_eq!(rng.seed, LONG_JUMPED);
+
+        let mut jumped = Xoshiro256Plus::new(SEED);
+        let mut advanced = jumped;
+        jumped.jump(BigUint::from(1000u32));
+        advanced.advance(BigUint::from(1000u32));
+        assert_eq!(jumped.seed, advanced.seed);
+    }
+
+    #[test]
+    fn distance_is_too_large_for_baby_step_giant_step() {
+        let mut rng = Xoshiro256Plus::new(SEED);
+        let mut other = rng;
+        other.next_state();
+        assert_eq!(rng.try_distance(other), Err(RngError::TooLarge));
+        assert_eq!(
+            Xoshiro256Plus::default().try_jump(BigUint::from(1u32)),
+            Err(RngError::ZeroState)
+        );
+    }
+}